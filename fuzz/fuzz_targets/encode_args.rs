@@ -0,0 +1,33 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Typed json args encoder, the produced messagepack must decode back.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use trinci_sign::{decode, encode};
+
+fuzz_target!(|data: &[u8]| {
+    let value = match serde_json::from_slice::<serde_json::Value>(data) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    if let Ok(buf) = encode::encode_json(value) {
+        decode::msgpack_to_json(&buf).expect("encoded args must decode");
+    }
+});
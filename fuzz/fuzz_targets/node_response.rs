@@ -0,0 +1,41 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of the node responses, as done by the subcommands talking to a node.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use trinci_core::{base::serialize::rmp_deserialize, Message};
+use trinci_sign::decode;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = decode::tx_response_outcome(data);
+
+    match rmp_deserialize::<Message>(data) {
+        Ok(Message::GetTransactionResponse { tx }) => {
+            let _ = decode::transaction_to_json(&tx);
+        }
+        Ok(Message::GetReceiptResponse { rx }) => {
+            let _ = decode::receipt_to_json(&rx, None);
+        }
+        Ok(Message::GetBlockResponse { block, txs }) => {
+            let _ = decode::block_to_json(&block, txs.as_ref());
+        }
+        _ => {}
+    }
+});
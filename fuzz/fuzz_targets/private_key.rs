@@ -0,0 +1,29 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Base58 pkcs8 private key parsing, up to the key pair creation.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use trinci_sign::{secret::PrivateKey, signer::LocalSigner};
+
+fuzz_target!(|text: &str| {
+    let private_key = PrivateKey::new(text.to_string());
+    let _ = private_key.to_pkcs8();
+    let _ = LocalSigner::new(&private_key);
+});
@@ -0,0 +1,32 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Unit tx args in every input format: json, messagepack, HEX and base58.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use trinci_sign::types::UnitTxArgs;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = UnitTxArgs::from_msgpack(data);
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = UnitTxArgs::from_json_string(text);
+        let _ = UnitTxArgs::from_hex_string(text);
+        let _ = UnitTxArgs::from_bs58_string(text);
+    }
+});
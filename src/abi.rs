@@ -0,0 +1,320 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Contract interface descriptors.
+//!
+//! The descriptors file is a json object keyed by the contract multihash (HEX):
+//! ```json
+//! {
+//!     "12205bdc...": {
+//!         "name": "asset",
+//!         "methods": {
+//!             "transfer": {
+//!                 "args": {
+//!                     "type": "map",
+//!                     "fields": {
+//!                         "from": { "type": "string" },
+//!                         "to": { "type": "string" },
+//!                         "units": { "type": "u64" }
+//!                     }
+//!                 },
+//!                 "returns": { "type": "nil" }
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_value::Value;
+
+use crate::{
+    encode::{BIN_HINT, BS58_HINT, I64_HINT, U64_HINT},
+    types::Result,
+};
+
+/// Type of a value exchanged with a contract
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TypeSchema {
+    #[default]
+    Any,
+    Nil,
+    Bool,
+    U64,
+    I64,
+    F64,
+    String,
+    Bin,
+    Array {
+        items: Box<TypeSchema>,
+    },
+    Map {
+        fields: BTreeMap<String, TypeSchema>,
+        #[serde(default)]
+        optional: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MethodAbi {
+    #[serde(default)]
+    pub args: TypeSchema,
+    #[serde(default)]
+    pub returns: TypeSchema,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ContractAbi {
+    #[serde(default)]
+    pub name: String,
+    pub methods: BTreeMap<String, MethodAbi>,
+}
+
+/// Contract descriptors keyed by contract multihash HEX
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AbiRegistry(BTreeMap<String, ContractAbi>);
+
+impl AbiRegistry {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        let registry: AbiRegistry = serde_json::from_slice(buf)?;
+        // Multihash HEX is case insensitive
+        Ok(AbiRegistry(
+            registry
+                .0
+                .into_iter()
+                .map(|(contract, abi)| (contract.to_lowercase(), abi))
+                .collect(),
+        ))
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+
+    /// Descriptor of a contract method.
+    /// Returns `None` if the contract is not registered and an error
+    /// if the contract is registered but the method is not declared.
+    pub fn method(&self, contract: &str, method: &str) -> Result<Option<&MethodAbi>> {
+        match self.0.get(&contract.to_lowercase()) {
+            Some(abi) => match abi.methods.get(method) {
+                Some(method_abi) => Ok(Some(method_abi)),
+                None => Err(format!(
+                    "method `{}` not declared for contract `{}` {}",
+                    method, abi.name, contract
+                )
+                .into()),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+fn type_error(path: &str, schema: &TypeSchema, value: &Value) -> String {
+    format!("{}: expected {:?}, found {:?}", path, schema, value)
+}
+
+fn hint_value<'a>(map: &'a BTreeMap<Value, Value>, hints: &[&str]) -> Option<(&'a str, &'a Value)> {
+    match map.iter().next() {
+        Some((Value::String(hint), value)) if map.len() == 1 && hints.contains(&hint.as_str()) => {
+            Some((hint.as_str(), value))
+        }
+        _ => None,
+    }
+}
+
+fn coerce_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::U8(val) => Some(*val as u64),
+        Value::U16(val) => Some(*val as u64),
+        Value::U32(val) => Some(*val as u64),
+        Value::U64(val) => Some(*val),
+        Value::I8(val) => u64::try_from(*val).ok(),
+        Value::I16(val) => u64::try_from(*val).ok(),
+        Value::I32(val) => u64::try_from(*val).ok(),
+        Value::I64(val) => u64::try_from(*val).ok(),
+        Value::String(text) => u64::from_str(text).ok(),
+        Value::Map(map) => hint_value(map, &[U64_HINT]).and_then(|(_, value)| coerce_u64(value)),
+        _ => None,
+    }
+}
+
+fn coerce_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::U8(val) => Some(*val as i64),
+        Value::U16(val) => Some(*val as i64),
+        Value::U32(val) => Some(*val as i64),
+        Value::U64(val) => i64::try_from(*val).ok(),
+        Value::I8(val) => Some(*val as i64),
+        Value::I16(val) => Some(*val as i64),
+        Value::I32(val) => Some(*val as i64),
+        Value::I64(val) => Some(*val),
+        Value::String(text) => i64::from_str(text).ok(),
+        Value::Map(map) => {
+            hint_value(map, &[I64_HINT, U64_HINT]).and_then(|(_, value)| coerce_i64(value))
+        }
+        _ => None,
+    }
+}
+
+fn coerce_bin(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::Bytes(bytes) => Some(bytes.clone()),
+        Value::String(text) => hex::decode(text).ok(),
+        Value::Map(map) => match hint_value(map, &[BIN_HINT, BS58_HINT]) {
+            Some((BIN_HINT, Value::String(text))) => hex::decode(text).ok(),
+            Some((_, Value::String(text))) => bs58::decode(text).into_vec().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check the value against the schema, converting it to the declared types
+/// (e.g. integers given as strings, binaries given as HEX or typed hints).
+pub fn coerce(
+    value: &Value,
+    schema: &TypeSchema,
+    path: &str,
+) -> std::result::Result<Value, String> {
+    let res = match (schema, value) {
+        (TypeSchema::Any, _) => Some(value.clone()),
+        (TypeSchema::Nil, Value::Unit | Value::Option(None)) => Some(Value::Unit),
+        (TypeSchema::Bool, Value::Bool(_)) => Some(value.clone()),
+        (TypeSchema::U64, _) => coerce_u64(value).map(Value::U64),
+        (TypeSchema::I64, _) => coerce_i64(value).map(Value::I64),
+        (TypeSchema::F64, Value::F32(val)) => Some(Value::F64(*val as f64)),
+        (TypeSchema::F64, Value::F64(_)) => Some(value.clone()),
+        (TypeSchema::F64, _) => coerce_i64(value).map(|val| Value::F64(val as f64)),
+        (TypeSchema::String, Value::String(_)) => Some(value.clone()),
+        (TypeSchema::String, Value::Char(val)) => Some(Value::String(val.to_string())),
+        (TypeSchema::Bin, _) => coerce_bin(value).map(Value::Bytes),
+        (TypeSchema::Array { items }, Value::Seq(seq)) => {
+            let mut res = vec![];
+            for (i, item) in seq.iter().enumerate() {
+                res.push(coerce(item, items, &format!("{}[{}]", path, i))?);
+            }
+            Some(Value::Seq(res))
+        }
+        (TypeSchema::Map { fields, optional }, Value::Map(map)) => {
+            let mut res = BTreeMap::new();
+            for (key, val) in map {
+                let name = match key {
+                    Value::String(name) => name,
+                    _ => return Err(format!("{}: unexpected key {:?}", path, key)),
+                };
+                match fields.get(name) {
+                    Some(field_schema) => {
+                        let val = coerce(val, field_schema, &format!("{}.{}", path, name))?;
+                        res.insert(key.clone(), val);
+                    }
+                    None => return Err(format!("{}: unexpected field `{}`", path, name)),
+                }
+            }
+            if let Some(name) = fields.keys().find(|name| {
+                !optional.contains(name) && !res.contains_key(&Value::String(name.to_string()))
+            }) {
+                return Err(format!("{}: missing field `{}`", path, name));
+            }
+            Some(Value::Map(res))
+        }
+        _ => None,
+    };
+    res.ok_or_else(|| type_error(path, schema, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118";
+
+    fn create_registry() -> AbiRegistry {
+        let descriptors = r#"{
+            "CONTRACT": {
+                "name": "asset",
+                "methods": {
+                    "transfer": {
+                        "args": {
+                            "type": "map",
+                            "fields": {
+                                "to": { "type": "string" },
+                                "units": { "type": "u64" },
+                                "memo": { "type": "bin" }
+                            },
+                            "optional": ["memo"]
+                        }
+                    }
+                }
+            }
+        }"#
+        .replace("CONTRACT", &CONTRACT.to_uppercase());
+        AbiRegistry::from_json_slice(descriptors.as_bytes()).unwrap()
+    }
+
+    fn json_to_value(json_text: &str) -> Value {
+        serde_json::from_str(json_text).unwrap()
+    }
+
+    #[test]
+    fn registry_method() {
+        let registry = create_registry();
+
+        assert!(registry.method(CONTRACT, "transfer").unwrap().is_some());
+        assert!(registry.method(CONTRACT, "tranfser").is_err());
+        assert!(registry.method("1220ab", "transfer").unwrap().is_none());
+    }
+
+    #[test]
+    fn coerce_args() {
+        let registry = create_registry();
+        let method = registry.method(CONTRACT, "transfer").unwrap().unwrap();
+        let args = json_to_value(r##"{"to":"#ACCOUNT","units":"100","memo":{"$bin":"0102"}}"##);
+
+        let res = coerce(&args, &method.args, "args").unwrap();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            Value::String("to".to_string()),
+            Value::String("#ACCOUNT".to_string()),
+        );
+        expected.insert(Value::String("units".to_string()), Value::U64(100));
+        expected.insert(Value::String("memo".to_string()), Value::Bytes(vec![1, 2]));
+        assert_eq!(res, Value::Map(expected));
+    }
+
+    #[test]
+    fn coerce_args_errors() {
+        let registry = create_registry();
+        let method = registry.method(CONTRACT, "transfer").unwrap().unwrap();
+
+        let args = json_to_value(r##"{"to":"#ACCOUNT"}"##);
+        let err = coerce(&args, &method.args, "args").unwrap_err();
+        assert_eq!(err, "args: missing field `units`");
+
+        let args = json_to_value(r##"{"to":"#ACCOUNT","units":-1}"##);
+        let err = coerce(&args, &method.args, "args").unwrap_err();
+        assert_eq!(err, "args.units: expected U64, found I64(-1)");
+
+        let args = json_to_value(r##"{"to":"#ACCOUNT","units":1,"amount":1}"##);
+        let err = coerce(&args, &method.args, "args").unwrap_err();
+        assert_eq!(err, "args: unexpected field `amount`");
+    }
+}
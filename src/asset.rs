@@ -0,0 +1,110 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_value::Value;
+
+/// Standard TRINCI asset operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetOperation {
+    Transfer {
+        from: String,
+        to: String,
+        units: u64,
+    },
+    Mint {
+        to: String,
+        units: u64,
+    },
+    Burn {
+        from: String,
+        units: u64,
+    },
+    Balance,
+}
+
+fn args_map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::String(key.to_string()), value))
+            .collect(),
+    )
+}
+
+impl AssetOperation {
+    /// Asset contract method
+    pub fn method(&self) -> &'static str {
+        match self {
+            AssetOperation::Transfer { .. } => "transfer",
+            AssetOperation::Mint { .. } => "mint",
+            AssetOperation::Burn { .. } => "burn",
+            AssetOperation::Balance => "balance",
+        }
+    }
+
+    /// Asset contract method arguments
+    pub fn args(&self) -> Value {
+        match self {
+            AssetOperation::Transfer { from, to, units } => args_map(vec![
+                ("from", Value::String(from.clone())),
+                ("to", Value::String(to.clone())),
+                ("units", Value::U64(*units)),
+            ]),
+            AssetOperation::Mint { to, units } => args_map(vec![
+                ("to", Value::String(to.clone())),
+                ("units", Value::U64(*units)),
+            ]),
+            AssetOperation::Burn { from, units } => args_map(vec![
+                ("from", Value::String(from.clone())),
+                ("units", Value::U64(*units)),
+            ]),
+            AssetOperation::Balance => Value::Unit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::{encode_args, encode_json};
+
+    #[test]
+    fn transfer_args() {
+        let operation = AssetOperation::Transfer {
+            from: String::from("#FROM"),
+            to: String::from("#TO"),
+            units: 100,
+        };
+
+        let res = encode_args(&operation.args()).unwrap();
+
+        let expected =
+            encode_json(serde_json::json!({"from": "#FROM", "to": "#TO", "units": 100})).unwrap();
+        assert_eq!(operation.method(), "transfer");
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn balance_args() {
+        let operation = AssetOperation::Balance;
+
+        let res = encode_args(&operation.args()).unwrap();
+
+        assert_eq!(operation.method(), "balance");
+        assert_eq!(res, [0xc0]);
+    }
+}
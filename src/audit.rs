@@ -0,0 +1,383 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Audit log of the produced signatures.
+//!
+//! The log is a json lines file, every entry holds the hash of the previous
+//! one (empty for the first entry) and its own hash, computed on the entry
+//! serialized with an empty `hash`. An edited, removed or reordered entry
+//! breaks the chain, except for the removal of the last entries: a truncated
+//! log is detected only against a checkpoint, the entries count and the head
+//! hash reported by a previous verification.
+//!
+//! The chain is not keyed: whoever can write the log can also rewrite it with
+//! a new valid chain. It is tamper-evident only against a checkpoint kept out
+//! of the reach of the log writers.
+//!
+//! The appends hold an exclusive lock on the file, the concurrent processes
+//! chain their entries one after the other.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use trinci_core::{
+    base::{schema::TransactionData, serialize::rmp_serialize},
+    crypto::{Hash, HashAlgorithm, Hashable},
+};
+
+use crate::{decode::hash_to_hex, types::Result};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AuditEntry {
+    /// Position in the log, starting from 0
+    pub seq: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Messagepacked caller public key HEX
+    pub public_key: String,
+    /// Caller account id
+    pub signer: String,
+    pub network: String,
+    pub target: String,
+    /// Contract multihash HEX, `None` for the account default one
+    pub contract: Option<String>,
+    pub method: String,
+    /// Sha256 multihash HEX of the messagepacked args
+    pub args_digest: String,
+    /// Transaction hash HEX
+    pub tx_hash: String,
+    /// Outcome of the operation, e.g. `created`, `signed` before a submission
+    /// or the submission `OK|...`/`KO|...`
+    pub outcome: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hash_to_hex(&Hash::from_data(HashAlgorithm::Sha256, data))
+}
+
+impl AuditEntry {
+    /// Entry of a unit transaction, a bulk root or a bulk node
+    fn new(tx_data: &TransactionData, outcome: &str) -> Result<Self> {
+        let (caller, network, account, contract, method, args) = match tx_data {
+            TransactionData::V1(data) | TransactionData::BulkRootV1(data) => (
+                &data.caller,
+                &data.network,
+                &data.account,
+                &data.contract,
+                &data.method,
+                &data.args,
+            ),
+            TransactionData::BulkNodeV1(data) => (
+                &data.caller,
+                &data.network,
+                &data.account,
+                &data.contract,
+                &data.method,
+                &data.args,
+            ),
+            _ => return Err("unexpected transaction data".into()),
+        };
+        Ok(AuditEntry {
+            seq: 0,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            public_key: hex::encode(rmp_serialize(caller)?),
+            signer: caller.to_account_id(),
+            network: network.clone(),
+            target: account.clone(),
+            contract: contract.as_ref().map(hash_to_hex),
+            method: method.clone(),
+            args_digest: sha256_hex(args),
+            tx_hash: hash_to_hex(&tx_data.primary_hash()),
+            outcome: outcome.to_string(),
+            prev_hash: String::new(),
+            hash: String::new(),
+        })
+    }
+
+    /// Hash of the entry, computed with an empty `hash` field
+    fn digest(&self) -> Result<String> {
+        let entry = AuditEntry {
+            hash: String::new(),
+            ..self.clone()
+        };
+        Ok(sha256_hex(&serde_json::to_vec(&entry)?))
+    }
+}
+
+/// Last non empty line of the file, read backwards from the end
+fn read_last_line(file: &mut File) -> Result<Option<String>> {
+    const CHUNK_LEN: u64 = 4096;
+
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut tail = vec![];
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_LEN);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+
+        let len = tail
+            .iter()
+            .rposition(|byte| !byte.is_ascii_whitespace())
+            .map_or(0, |pos| pos + 1);
+        if let Some(pos) = tail[..len].iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(String::from_utf8(tail[pos + 1..len].to_vec())?));
+        }
+        if end == 0 && len > 0 {
+            return Ok(Some(String::from_utf8(tail[..len].to_vec())?));
+        }
+    }
+    Ok(None)
+}
+
+/// Append the entry of a produced signature to the log, creating the file if missing
+pub fn append(path: &str, tx_data: &TransactionData, outcome: &str) -> Result<()> {
+    let mut entry = AuditEntry::new(tx_data, outcome)?;
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    // Released when the file is closed, after the entry is written
+    file.lock_exclusive()?;
+
+    if let Some(line) = read_last_line(&mut file)? {
+        let last: AuditEntry = serde_json::from_str(&line)
+            .map_err(|e| format!("invalid last entry of the audit log: {}", e))?;
+        entry.seq = last.seq + 1;
+        entry.prev_hash = last.hash;
+    }
+    entry.hash = entry.digest()?;
+
+    let mut line = serde_json::to_vec(&entry)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// Entries count and hash of the last entry of a verified log
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Checkpoint {
+    pub count: u64,
+    /// Empty for an empty log
+    pub head: String,
+}
+
+/// Check the log chain, returning its checkpoint. With the checkpoint of a
+/// previous verification the log must still hold its head entry
+pub fn verify(text: &str, checkpoint: Option<&Checkpoint>) -> Result<Checkpoint> {
+    let mut prev_hash = String::new();
+    let mut count = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let entry: AuditEntry =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if entry.seq != count {
+            return Err(format!(
+                "line {}: expected seq {}, found {}",
+                i + 1,
+                count,
+                entry.seq
+            )
+            .into());
+        }
+        if entry.prev_hash != prev_hash {
+            return Err(format!("line {}: broken chain, previous hash mismatch", i + 1).into());
+        }
+        if entry.hash != entry.digest()? {
+            return Err(format!("line {}: entry hash mismatch", i + 1).into());
+        }
+        prev_hash = entry.hash;
+        count += 1;
+        if let Some(checkpoint) = checkpoint {
+            if count == checkpoint.count && !prev_hash.eq_ignore_ascii_case(&checkpoint.head) {
+                return Err(format!(
+                    "line {}: entry hash differs from the checkpoint head",
+                    i + 1
+                )
+                .into());
+            }
+        }
+    }
+
+    if let Some(checkpoint) = checkpoint {
+        if count < checkpoint.count {
+            return Err(format!(
+                "truncated log: {} entries, the checkpoint has {}",
+                count, checkpoint.count
+            )
+            .into());
+        }
+    }
+    Ok(Checkpoint {
+        count,
+        head: prev_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trinci_core::{
+        crypto::ecdsa::{CurveId, PublicKey as EcdsaPublicKey},
+        PublicKey, TransactionDataV1,
+    };
+
+    fn create_log(len: u64) -> String {
+        let mut prev_hash = String::new();
+        let mut lines = vec![];
+        for seq in 0..len {
+            let mut entry = AuditEntry {
+                seq,
+                timestamp: 1650000000 + seq,
+                public_key: String::from("93a56563647361"),
+                signer: String::from("QmSigner"),
+                network: String::from("SKYNET"),
+                target: String::from("#MYASSET"),
+                contract: None,
+                method: String::from("transfer"),
+                args_digest: sha256_hex(&[0xc0]),
+                tx_hash: String::from("1220"),
+                outcome: String::from("created"),
+                prev_hash: prev_hash.clone(),
+                hash: String::new(),
+            };
+            entry.hash = entry.digest().unwrap();
+            prev_hash = entry.hash.clone();
+            lines.push(serde_json::to_string(&entry).unwrap());
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn concurrent_appends() {
+        let path = std::env::temp_dir()
+            .join(format!("trinci_sign_{}_audit.jsonl", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let tx_data = TransactionData::V1(TransactionDataV1 {
+            account: String::from("#MYASSET"),
+            fuel_limit: 1000,
+            nonce: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            network: String::from("SKYNET"),
+            contract: None,
+            method: String::from("transfer"),
+            caller: PublicKey::Ecdsa(EcdsaPublicKey {
+                curve_id: CurveId::Secp384R1,
+                value: vec![4; 97],
+            }),
+            args: vec![0xc0],
+        });
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let path = path.clone();
+                let tx_data = tx_data.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        append(&path, &tx_data, "signed").unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap_or_default();
+        assert_eq!(verify(&text, None).unwrap().count, 40);
+    }
+
+    #[test]
+    fn verify_log() {
+        let log = create_log(3);
+
+        let checkpoint = verify(&log, None).unwrap();
+
+        assert_eq!(checkpoint.count, 3);
+        let last: AuditEntry = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+        assert_eq!(checkpoint.head, last.hash);
+    }
+
+    #[test]
+    fn verify_log_against_checkpoint() {
+        let checkpoint = verify(&create_log(3), None).unwrap();
+
+        // Grown since the checkpoint
+        let res = verify(&create_log(5), Some(&checkpoint)).unwrap();
+        assert_eq!(res.count, 5);
+
+        let other = Checkpoint {
+            count: 3,
+            head: String::from("1220"),
+        };
+        let err = verify(&create_log(5), Some(&other)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3: entry hash differs from the checkpoint head"
+        );
+    }
+
+    #[test]
+    fn verify_truncated_log() {
+        let log = create_log(3);
+        let checkpoint = verify(&log, None).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        let log = lines[..2].join("\n");
+
+        // The chain of the remaining entries is intact
+        assert_eq!(verify(&log, None).unwrap().count, 2);
+        let err = verify(&log, Some(&checkpoint)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "truncated log: 2 entries, the checkpoint has 3"
+        );
+    }
+
+    #[test]
+    fn verify_edited_log() {
+        let log = create_log(3).replacen("SKYNET", "OTHERNET", 1);
+
+        let err = verify(&log, None).unwrap_err();
+
+        assert_eq!(err.to_string(), "line 1: entry hash mismatch");
+    }
+
+    #[test]
+    fn verify_log_with_removed_entry() {
+        let log = create_log(3);
+        let lines: Vec<&str> = log.lines().collect();
+        let log = [lines[0], lines[2]].join("\n");
+
+        let err = verify(&log, None).unwrap_err();
+
+        assert_eq!(err.to_string(), "line 2: expected seq 1, found 2");
+    }
+}
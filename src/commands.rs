@@ -0,0 +1,1950 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Subcommands of the `trinci-sign` binary, called with the parsed command
+//! line arguments. The outputs are written to the stdout as `OK|...`/`KO|...`.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, BufRead, Write},
+    time::Duration,
+};
+
+use trinci_core::{
+    base::{
+        schema::{
+            BulkTransaction, BulkTransactions, Receipt, SignedTransaction, TransactionData,
+            TransactionDataBulkNodeV1, TransactionDataBulkV1, UnsignedTransaction,
+        },
+        serialize::{rmp_deserialize, rmp_serialize, MessagePack},
+    },
+    crypto::{Hash, Hashable},
+    ErrorKind, Message, PublicKey, TransactionDataV1,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    abi::{self, AbiRegistry, TypeSchema},
+    audit, decode, encode,
+    fuel::{self, FuelRecord},
+    http_channel::HttpChannel,
+    mock_node,
+    multisig::{Approval, MultisigCommand, Proposal},
+    payout::{self, OUTCOME_PENDING, OUTCOME_RECEIPT_ERROR, OUTCOME_SUCCESS, OUTCOME_UNKNOWN},
+    policy, remote_signer,
+    secret::PrivateKey,
+    signer::{LocalSigner, Signer, SignerConfig},
+    types::{AppOptions, Arguments, Encoding, NodeInfo, Result, UnitTxArgs},
+    validation::validate_unit_tx_args,
+    vectors::{self, Expected, TestVector},
+    wizard::{self, Profile, Prompter, ReplCommand, REPL_HELP},
+};
+
+/// Receipt requests while waiting for the transaction execution
+const RECEIPT_POLLS: usize = 30;
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+fn get_node_info(url: &str) -> Result<NodeInfo> {
+    let mut info = NodeInfo::default();
+
+    if let Message::GetCoreStatsResponse((_, pending_txs, last_block)) =
+        send_message(url.to_string(), &Message::GetCoreStatsRequest)?
+    {
+        info.pending_txs = Some(pending_txs);
+        info.height = last_block.map(|block| block.data.height);
+    }
+
+    // The REST endpoints are optional, a node may expose only some of them
+    let http_channel = HttpChannel::new(url.to_string());
+    for path in ["/visa", "/status"] {
+        if let Ok(buf) = http_channel.get(path) {
+            if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&buf) {
+                info.update_from_json(&value);
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+pub fn node_info(url: String) -> Result<()> {
+    let info = get_node_info(&url)?;
+    let output = format!("OK|{}", serde_json::to_string(&info)?);
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+fn check_network(input_args: &Arguments, url: &str) -> Result<()> {
+    let network = match input_args {
+        Arguments::UnitTxArgsType(args) => &args.network,
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    match get_node_info(url)?.network {
+        Some(node_network) if node_network == *network => Ok(()),
+        Some(node_network) => Err(format!(
+            "network mismatch: args `{}`, node `{}`",
+            network, node_network
+        )
+        .into()),
+        None => Err("unable to retrieve the node network".into()),
+    }
+}
+
+pub fn submit_unit_tx(input_args: Arguments, url: String, options: AppOptions) -> Result<()> {
+    if let Arguments::UnitTxBatchArgs(txs_args) = input_args {
+        return submit_unit_tx_batch(txs_args, url, &options);
+    }
+    if options.dry_run {
+        return dry_run_unit_tx(input_args, &options);
+    }
+    if options.check_network {
+        check_network(&input_args, &url)?;
+    }
+    let (sign_tx, tx) = create_unit_tx_message(input_args, &options)?;
+    // Recorded before sending, the outcome may never be known
+    audit(&options, &sign_tx.data, "signed")?;
+    let res = send_tx(tx, url);
+    let outcome = match &res {
+        Ok(output) => output.clone(),
+        Err(e) => format!("KO|{}", e),
+    };
+    audit(&options, &sign_tx.data, &outcome)?;
+    let output = res?;
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Submit a transaction for each row, printing a line with the outcome of each
+fn submit_unit_tx_batch(
+    txs_args: Vec<UnitTxArgs>,
+    url: String,
+    options: &AppOptions,
+) -> Result<()> {
+    let mut lines = vec![];
+    for (sign_tx, tx) in create_batch_messages(txs_args, options)? {
+        audit(options, &sign_tx.data, "signed")?;
+        let outcome = match send_tx(tx, url.clone()) {
+            Ok(output) => output,
+            Err(e) => format!("KO|{}", e),
+        };
+        audit(options, &sign_tx.data, &outcome)?;
+        lines.push(outcome);
+    }
+    io::stdout()
+        .write_all(lines.join("\n").as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Send the transaction message to the node, returning the outcome as `OK|...` or `KO|...`
+fn send_tx(tx: Vec<u8>, url: String) -> Result<String> {
+    let mut http_channel = HttpChannel::new(url);
+    http_channel.send(tx)?;
+    let buf = http_channel.recv()?;
+
+    decode::tx_response_outcome(&buf)
+}
+
+pub fn multisig(input_args: Arguments, url: String, options: AppOptions) -> Result<()> {
+    let command = match input_args {
+        Arguments::MultisigArgs(command) => command,
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    let output = match command {
+        MultisigCommand::Propose {
+            tx,
+            signers,
+            proposal: path,
+        } => {
+            let proposal = Proposal::new(tx, signers)?;
+            proposal.save(&path)?;
+            format!("OK|{}", proposal.id)
+        }
+        MultisigCommand::Approve {
+            proposal: path,
+            private_key,
+        } => {
+            let mut proposal = Proposal::load(&path)?;
+            let sign_tx = create_signed_tx(proposal.tx.to_unit_tx_args(private_key), &options)?;
+            audit(
+                &options,
+                &sign_tx.data,
+                &format!("approved {}", proposal.id),
+            )?;
+            let approval = create_approval(sign_tx)?;
+            let signer = approval.signer.clone();
+
+            proposal.add_approval(approval)?;
+            proposal.save(&path)?;
+            format!("OK|{}|{} missing", signer, proposal.missing().len())
+        }
+        MultisigCommand::Status { proposal: path } => {
+            let proposal = Proposal::load(&path)?;
+            let approved: Vec<&String> = proposal
+                .approvals
+                .iter()
+                .map(|approval| &approval.signer)
+                .collect();
+            format!(
+                "OK|{}",
+                serde_json::json!({
+                    "id": proposal.id,
+                    "approved": approved,
+                    "missing": proposal.missing(),
+                })
+            )
+        }
+        MultisigCommand::Submit { proposal: path } => {
+            submit_approvals(&Proposal::load(&path)?, &url, &options)?.join("\n")
+        }
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Submit the collected approvals in the signers order, returning an output
+/// line for each and one for the missing approvals
+fn submit_approvals(proposal: &Proposal, url: &str, options: &AppOptions) -> Result<Vec<String>> {
+    // Nothing is sent unless every collected approval is valid
+    let mut txs = vec![];
+    let mut errors = vec![];
+    for signer in &proposal.signers {
+        if let Some(approval) = proposal.approval(signer) {
+            match check_approval(proposal, approval) {
+                Ok(tx) => txs.push((signer, tx)),
+                Err(e) => errors.push(format!("approval of `{}`: {}", signer, e)),
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("; ").into());
+    }
+
+    // A failed submission does not stop the others
+    let mut lines = vec![];
+    for (signer, (sign_tx, buf)) in txs {
+        let outcome = match send_tx(buf, url.to_string()) {
+            Ok(output) => output,
+            Err(e) => format!("KO|{}", e),
+        };
+        audit(options, &sign_tx.data, &outcome)?;
+        lines.push(format!("{}|{}", outcome, signer));
+    }
+    let missing = proposal.missing();
+    if !missing.is_empty() {
+        let missing: Vec<&str> = missing.iter().map(|signer| signer.as_str()).collect();
+        lines.push(format!("KO|missing approvals: {}", missing.join(", ")));
+    }
+    Ok(lines)
+}
+
+/// Approval of the signed proposal transaction, with its messagepacked put request
+fn create_approval(sign_tx: SignedTransaction) -> Result<Approval> {
+    let signer = match &sign_tx.data {
+        TransactionData::V1(data) => data.caller.to_account_id(),
+        _ => return Err("unexpected transaction data".into()),
+    };
+    let hash = decode::hash_to_hex(&sign_tx.data.primary_hash());
+    let tx = trinci_core::Transaction::UnitTransaction(sign_tx);
+    let buf = rmp_serialize(&Message::PutTransactionRequest { confirm: true, tx })?;
+
+    Ok(Approval {
+        signer,
+        hash,
+        tx: hex::encode(buf),
+    })
+}
+
+/// Decode an approval and check that it holds the proposal transaction, signed
+/// by the listed signer. Returns the transaction with the message to send.
+fn check_approval(
+    proposal: &Proposal,
+    approval: &Approval,
+) -> Result<(SignedTransaction, Vec<u8>)> {
+    let buf = hex::decode(&approval.tx)?;
+    let sign_tx = match rmp_deserialize::<Message>(&buf)? {
+        Message::PutTransactionRequest {
+            tx: trinci_core::Transaction::UnitTransaction(sign_tx),
+            ..
+        } => sign_tx,
+        _ => return Err("not a unit transaction request".into()),
+    };
+    let data = match &sign_tx.data {
+        TransactionData::V1(data) => data,
+        _ => return Err("unexpected transaction data".into()),
+    };
+
+    let template = &proposal.tx;
+    let contract = if template.contract.is_empty() {
+        None
+    } else {
+        Some(Hash::from_hex(&template.contract)?)
+    };
+    let fields = [
+        ("target", data.account == template.target),
+        ("network", data.network == template.network),
+        ("fuel", data.fuel_limit == template.fuel),
+        ("contract", data.contract == contract),
+        ("method", data.method == template.method),
+        ("args", data.args == encode::encode_args(&template.args)?),
+    ];
+    let mut errors: Vec<String> = fields
+        .iter()
+        .filter(|(_, same)| !same)
+        .map(|(name, _)| format!("{} differs from the proposal", name))
+        .collect();
+    let caller = data.caller.to_account_id();
+    if caller != approval.signer {
+        errors.push(format!("signed by `{}`", caller));
+    }
+    if !decode::hash_to_hex(&sign_tx.data.primary_hash()).eq_ignore_ascii_case(&approval.hash) {
+        errors.push(String::from("hash differs from the transaction one"));
+    }
+    if let Err(e) = verify_signed_tx(&sign_tx) {
+        errors.push(e.to_string());
+    }
+
+    if errors.is_empty() {
+        Ok((sign_tx, buf))
+    } else {
+        Err(errors.join(", ").into())
+    }
+}
+
+/// Send the message to the node, returning the answer exceptions included
+fn request_message(url: String, message: &Message) -> Result<Message> {
+    let buf = rmp_serialize(message)?;
+    let mut http_channel = HttpChannel::new(url);
+    http_channel.send(buf)?;
+    let buf = http_channel.recv()?;
+
+    rmp_deserialize::<Message>(&buf).map_err(|e| e.into())
+}
+
+fn send_message(url: String, message: &Message) -> Result<Message> {
+    match request_message(url, message)? {
+        Message::Exception(e) => Err(e.into()),
+        msg => Ok(msg),
+    }
+}
+
+/// Wait for the receipt of a submitted transaction
+fn wait_receipt(url: &str, hash: Hash) -> Result<Receipt> {
+    for _ in 0..RECEIPT_POLLS {
+        match request_message(url.to_string(), &Message::GetReceiptRequest { hash })? {
+            Message::GetReceiptResponse { rx } => return Ok(rx),
+            Message::Exception(e) if matches!(e.kind, ErrorKind::ResourceNotFound) => {
+                std::thread::sleep(RECEIPT_POLL_INTERVAL)
+            }
+            Message::Exception(e) => return Err(e.into()),
+            msg => return Err(format!("unexpected message {:?}", msg).into()),
+        }
+    }
+    Err("receipt not available yet, the transaction may still be pending".into())
+}
+
+pub fn get_block(input_args: Arguments, url: String) -> Result<()> {
+    let (height, txs) = match input_args {
+        Arguments::GetBlockArgs { height, txs } => (height, txs),
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    let output = match send_message(url, &Message::GetBlockRequest { height, txs })? {
+        Message::GetBlockResponse { block, txs } => {
+            format!("OK|{}", decode::block_to_json(&block, txs.as_ref()))
+        }
+        msg => format!("KO|{:?}", msg),
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+pub fn get_tx(input_args: Arguments, url: String) -> Result<()> {
+    let hash = match input_args {
+        Arguments::GetTxArgs { hash } => Hash::from_hex(&hash)?,
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    let output = match send_message(url, &Message::GetTransactionRequest { hash })? {
+        Message::GetTransactionResponse { tx } => {
+            format!("OK|{}", decode::transaction_to_json(&tx))
+        }
+        msg => format!("KO|{:?}", msg),
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Contract multihash HEX (empty for the account default one) and method called by a transaction
+fn get_tx_call(url: String, hash: Hash) -> Result<Option<(String, String)>> {
+    let tx = match send_message(url, &Message::GetTransactionRequest { hash })? {
+        Message::GetTransactionResponse { tx } => tx,
+        msg => return Err(format!("unexpected response {:?}", msg).into()),
+    };
+
+    let data = match tx {
+        trinci_core::Transaction::UnitTransaction(tx) => tx.data,
+        _ => return Ok(None),
+    };
+    match data {
+        TransactionData::V1(TransactionDataV1 {
+            contract, method, ..
+        }) => Ok(Some((
+            contract
+                .as_ref()
+                .map(decode::hash_to_hex)
+                .unwrap_or_default(),
+            method,
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Declared return type of the method called by a transaction
+fn get_returns_schema(abi: &AbiRegistry, call: &(String, String)) -> Result<Option<TypeSchema>> {
+    let (contract, method) = call;
+    if contract.is_empty() {
+        return Ok(None);
+    }
+    Ok(abi
+        .method(contract, method)?
+        .map(|method_abi| method_abi.returns.clone()))
+}
+
+/// Record the burned fuel of an executed transaction, if requested
+fn record_fuel(options: &AppOptions, call: &(String, String), rx: &Receipt) -> Result<()> {
+    match &options.fuel_history {
+        Some(path) => fuel::record(path, &FuelRecord::new(&call.0, &call.1, rx)),
+        None => Ok(()),
+    }
+}
+
+pub fn get_receipt(input_args: Arguments, url: String, options: AppOptions) -> Result<()> {
+    let hash = match input_args {
+        Arguments::GetReceiptArgs { hash } => hash,
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    let hash = Hash::from_hex(&hash)?;
+    let call = if options.abi.is_some() || options.fuel_history.is_some() {
+        get_tx_call(url.clone(), hash)?
+    } else {
+        None
+    };
+    let returns_schema = match (&options.abi, &call) {
+        (Some(abi), Some(call)) => get_returns_schema(abi, call)?,
+        _ => None,
+    };
+
+    let request = Message::GetReceiptRequest { hash };
+    let output = match send_message(url, &request)? {
+        Message::GetReceiptResponse { rx } => {
+            if let Some(call) = &call {
+                record_fuel(&options, call, &rx)?;
+            }
+            format!(
+                "OK|{}",
+                decode::receipt_to_json(&rx, returns_schema.as_ref())
+            )
+        }
+        msg => format!("KO|{:?}", msg),
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+fn create_signed_tx(input_args: UnitTxArgs, options: &AppOptions) -> Result<SignedTransaction> {
+    let nonce = rand::random::<u64>().to_be_bytes().to_vec();
+    create_signed_tx_with_nonce(input_args, options, nonce)
+}
+
+/// Sign the transaction with the given nonce, fixed only by the test vectors
+fn create_signed_tx_with_nonce(
+    input_args: UnitTxArgs,
+    options: &AppOptions,
+    nonce: Vec<u8>,
+) -> Result<SignedTransaction> {
+    let signer = create_signer(&input_args.private_key, options)?;
+    sign_unit_tx(input_args, options, nonce, signer.as_ref())
+}
+
+fn sign_unit_tx(
+    input_args: UnitTxArgs,
+    options: &AppOptions,
+    nonce: Vec<u8>,
+    signer: &dyn Signer,
+) -> Result<SignedTransaction> {
+    let data = prepare_tx_data(input_args, options, nonce, signer)?;
+
+    let data = TransactionData::V1(data);
+    let bytes = data.serialize();
+    let signature = signer.sign(&bytes)?;
+
+    Ok(SignedTransaction { data, signature })
+}
+
+/// Signer of the configured backend, the local one with the private key by default
+fn create_signer(private_key: &PrivateKey, options: &AppOptions) -> Result<Box<dyn Signer>> {
+    match &options.signer {
+        Some(config) => config.create_signer(private_key),
+        None => Ok(Box::new(LocalSigner::new(private_key)?)),
+    }
+}
+
+/// Validate the args and build the transaction data, the caller is the signer public key
+fn prepare_tx_data(
+    mut input_args: UnitTxArgs,
+    options: &AppOptions,
+    nonce: Vec<u8>,
+    signer: &dyn Signer,
+) -> Result<TransactionDataV1> {
+    if let Some(abi) = &options.abi {
+        if let Some(method_abi) = abi.method(&input_args.contract, &input_args.method)? {
+            input_args.args = abi::coerce(&input_args.args, &method_abi.args, "args")?;
+        }
+    }
+
+    if let Some(setting) = &options.fuel {
+        let history = match &options.fuel_history {
+            Some(path) => fuel::load_history(path)?,
+            None => vec![],
+        };
+        let choice = setting.choose(&history, &input_args);
+        eprintln!("Fuel limit: {}", choice);
+        input_args.fuel = choice.fuel;
+    }
+
+    if !options.lenient {
+        validate_unit_tx_args(&input_args, &options.fuel_bounds)?;
+    }
+
+    let contract = if input_args.contract.is_empty() {
+        None
+    } else if options.lenient {
+        Hash::from_hex(&input_args.contract).ok()
+    } else {
+        Some(Hash::from_hex(&input_args.contract)?)
+    };
+
+    let caller = signer.public_key()?;
+
+    let args = encode::encode_args(&input_args.args)?;
+
+    let data = TransactionDataV1 {
+        account: input_args.target,
+        fuel_limit: input_args.fuel,
+        nonce,
+        network: input_args.network,
+        contract,
+        method: input_args.method,
+        caller,
+        args,
+    };
+
+    // Checked on the data to be signed, as normalized and encoded
+    if let Some(policy) = &options.policy {
+        policy.check(&data)?;
+    }
+
+    Ok(data)
+}
+
+/// Pack the transactions in a bulk one: the first is the unsigned root, the
+/// others are nodes depending on it, all signed by the signer
+fn create_bulk_tx(
+    txs_args: Vec<UnitTxArgs>,
+    options: &AppOptions,
+    signer: &dyn Signer,
+) -> Result<BulkTransaction> {
+    let mut root: Option<TransactionData> = None;
+    let mut nodes = vec![];
+    for input_args in txs_args {
+        let nonce = rand::random::<u64>().to_be_bytes().to_vec();
+        let data = prepare_tx_data(input_args, options, nonce, signer)?;
+        let root_hash = match &root {
+            Some(root_data) => root_data.primary_hash(),
+            None => {
+                root = Some(TransactionData::BulkRootV1(data));
+                continue;
+            }
+        };
+
+        let node = TransactionData::BulkNodeV1(TransactionDataBulkNodeV1 {
+            account: data.account,
+            fuel_limit: data.fuel_limit,
+            nonce: data.nonce,
+            network: data.network,
+            contract: data.contract,
+            method: data.method,
+            caller: data.caller,
+            args: data.args,
+            depends_on: root_hash,
+        });
+        let signature = signer.sign(&node.serialize())?;
+        nodes.push(SignedTransaction {
+            data: node,
+            signature,
+        });
+    }
+
+    let root = root.ok_or("empty bulk transaction")?;
+    let data = TransactionData::BulkV1(TransactionDataBulkV1 {
+        txs: BulkTransactions {
+            root: Box::new(UnsignedTransaction { data: root }),
+            nodes: Some(nodes),
+        },
+    });
+    let signature = signer.sign(&data.serialize())?;
+
+    Ok(BulkTransaction { data, signature })
+}
+
+/// Verify the transaction signature against the caller public key
+fn verify_signed_tx(sign_tx: &SignedTransaction) -> Result<()> {
+    let caller = match &sign_tx.data {
+        TransactionData::V1(data) => &data.caller,
+        _ => return Err("unexpected transaction data".into()),
+    };
+
+    if caller.verify(&sign_tx.data.serialize(), &sign_tx.signature) {
+        Ok(())
+    } else {
+        Err("signature verification failed".into())
+    }
+}
+
+/// Record the produced signature in the audit log, if requested
+fn audit(options: &AppOptions, tx_data: &TransactionData, outcome: &str) -> Result<()> {
+    match &options.audit_log {
+        Some(path) => audit::append(path, tx_data, outcome),
+        None => Ok(()),
+    }
+}
+
+/// Data of the signed transactions, the root and the nodes of a bulk one
+fn signed_tx_data(tx: &trinci_core::Transaction) -> Vec<&TransactionData> {
+    match tx {
+        trinci_core::Transaction::UnitTransaction(sign_tx) => vec![&sign_tx.data],
+        trinci_core::Transaction::BulkTransaction(bulk_tx) => match &bulk_tx.data {
+            TransactionData::BulkV1(data) => {
+                let mut txs_data = vec![&data.txs.root.data];
+                if let Some(nodes) = &data.txs.nodes {
+                    txs_data.extend(nodes.iter().map(|node| &node.data));
+                }
+                txs_data
+            }
+            _ => vec![],
+        },
+    }
+}
+
+/// Sign the transaction, returning it together with its messagepacked put request
+fn create_unit_tx_message(
+    input_args: Arguments,
+    options: &AppOptions,
+) -> Result<(SignedTransaction, Vec<u8>)> {
+    match input_args {
+        Arguments::UnitTxArgsType(input_args) => {
+            let sign_tx = create_signed_tx(input_args, options)?;
+
+            let tx = trinci_core::Transaction::UnitTransaction(sign_tx.clone());
+
+            let message = Message::PutTransactionRequest { confirm: true, tx };
+
+            // Message pack of the transaction
+            let buf = rmp_serialize(&message)?;
+
+            Ok((sign_tx, buf))
+        }
+        _ => Err("unexpected arguments".into()),
+    }
+}
+
+/// Sign the transaction of every row, the invalid rows are reported together
+/// before any output
+fn create_batch_messages(
+    txs_args: Vec<UnitTxArgs>,
+    options: &AppOptions,
+) -> Result<Vec<(SignedTransaction, Vec<u8>)>> {
+    let mut messages = Vec::with_capacity(txs_args.len());
+    let mut errors = vec![];
+    for (i, args) in txs_args.into_iter().enumerate() {
+        match create_unit_tx_message(Arguments::UnitTxArgsType(args), options) {
+            Ok(message) => messages.push(message),
+            Err(e) => errors.push(format!("row {}: {}", i + 1, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(messages)
+    } else {
+        Err(errors.join("; ").into())
+    }
+}
+
+/// Performs all the local steps of a submission without contacting the node
+fn dry_run_unit_tx(input_args: Arguments, options: &AppOptions) -> Result<()> {
+    let input_args = match input_args {
+        Arguments::UnitTxArgsType(input_args) => input_args,
+        _ => return Err("unexpected arguments".into()),
+    };
+
+    // `--lenient` conflicts with `--dry-run`, the args are always validated here
+    let sign_tx = create_signed_tx(input_args, options)?;
+    verify_signed_tx(&sign_tx)?;
+    audit(options, &sign_tx.data, "dry-run")?;
+
+    // Check that the message sent to the node decodes back to the same transaction
+    let tx = trinci_core::Transaction::UnitTransaction(sign_tx);
+    let buf = rmp_serialize(&Message::PutTransactionRequest { confirm: true, tx })?;
+    let tx = match rmp_deserialize::<Message>(&buf)? {
+        Message::PutTransactionRequest { tx, .. } => tx,
+        _ => return Err("transaction message decoding failed".into()),
+    };
+
+    let output = format!("OK|{}", decode::transaction_to_json(&tx));
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Write the produced bytes with the requested encoding on the stdout or in the `--out` file
+fn write_output(buf: &[u8], options: &AppOptions, default_encoding: Encoding) -> Result<()> {
+    let buf = options.encoding.unwrap_or(default_encoding).encode(buf);
+    match &options.out {
+        Some(path) => std::fs::write(path, buf)?,
+        None => io::stdout().write_all(&buf).unwrap_or_default(),
+    }
+    Ok(())
+}
+
+pub fn convert_string_to_msgpack(input_args: String, options: AppOptions) -> Result<()> {
+    let args = rmp_serialize(&input_args)?;
+    write_output(&args, &options, Encoding::Bytes)
+}
+pub fn convert_json_struct_to_msgpack(
+    input_args: serde_json::Value,
+    options: AppOptions,
+) -> Result<()> {
+    let args = encode::encode_json(input_args)?;
+    write_output(&args, &options, Encoding::Bytes)
+}
+
+pub fn convert_msgpack_to_json(buf: Vec<u8>) -> Result<()> {
+    let (value, non_string_keys) = decode::msgpack_to_json(&buf)?;
+    if non_string_keys {
+        eprintln!("Warning: maps with non-string keys, converted as `$map` entries");
+    }
+    let value = serde_json::to_string(&value)?;
+    io::stdout().write_all(value.as_bytes()).unwrap_or_default();
+
+    Ok(())
+}
+
+pub fn encode_unit_tx_args(input_args: Arguments, options: AppOptions) -> Result<()> {
+    let args = match input_args {
+        Arguments::UnitTxArgsType(args) => args,
+        _ => return Err("unexpected arguments".into()),
+    };
+    if !options.lenient {
+        validate_unit_tx_args(&args, &options.fuel_bounds)?;
+    }
+    let buf = Zeroizing::new(args.to_msgpack()?);
+    write_output(&buf, &options, Encoding::Hex)
+}
+
+pub fn decode_unit_tx_args(input_args: Arguments) -> Result<()> {
+    let args = match input_args {
+        Arguments::UnitTxArgsType(args) => args,
+        _ => return Err("unexpected arguments".into()),
+    };
+    let value = Zeroizing::new(serde_json::to_string(&decode::unit_tx_args_to_json(
+        &args,
+    )?)?);
+    io::stdout().write_all(value.as_bytes()).unwrap_or_default();
+
+    Ok(())
+}
+
+pub fn create_unit_tx(input_args: Arguments, options: AppOptions) -> Result<()> {
+    if let Arguments::UnitTxBatchArgs(txs_args) = input_args {
+        return create_unit_tx_batch(txs_args, &options);
+    }
+    let (sign_tx, tx) = create_unit_tx_message(input_args, &options)?;
+    audit(&options, &sign_tx.data, "created")?;
+    write_output(&tx, &options, Encoding::Raw)
+}
+
+/// Write a line with the encoded transaction of each row
+fn create_unit_tx_batch(txs_args: Vec<UnitTxArgs>, options: &AppOptions) -> Result<()> {
+    let encoding = options.encoding.unwrap_or(Encoding::Hex);
+    if encoding == Encoding::Raw {
+        return Err(
+            "the raw transactions of the rows cannot be told apart, use a text encoding".into(),
+        );
+    }
+
+    let mut lines = vec![];
+    for (sign_tx, tx) in create_batch_messages(txs_args, options)? {
+        audit(options, &sign_tx.data, "created")?;
+        lines.push(encoding.encode(&tx));
+    }
+    let buf = lines.join(&b'\n');
+    match &options.out {
+        Some(path) => std::fs::write(path, buf)?,
+        None => io::stdout().write_all(&buf).unwrap_or_default(),
+    }
+    Ok(())
+}
+
+pub fn audit_verify(input_args: Arguments) -> Result<()> {
+    let (log, checkpoint) = match input_args {
+        Arguments::AuditVerifyArgs { log, checkpoint } => (log, checkpoint),
+        _ => return Err("unexpected arguments".into()),
+    };
+    let res = audit::verify(&std::fs::read_to_string(log)?, checkpoint.as_ref())?;
+
+    let output = format!("OK|{} entries, head {}", res.count, res.head);
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+pub fn signer_server(input_args: Arguments) -> Result<()> {
+    match input_args {
+        Arguments::SignerServerArgs { listen, config } => remote_signer::serve(&listen, &config),
+        _ => Err("unexpected arguments".into()),
+    }
+}
+
+/// Derive the bytes of a test vector, with a fresh signature
+fn derive_test_vector(vector: &TestVector) -> Result<Expected> {
+    let nonce = hex::decode(&vector.nonce)?;
+    let sign_tx = create_signed_tx_with_nonce(vector.args.clone(), &AppOptions::default(), nonce)?;
+    verify_signed_tx(&sign_tx)?;
+
+    let public_key = match &sign_tx.data {
+        TransactionData::V1(data) => rmp_serialize(&data.caller)?,
+        _ => return Err("unexpected transaction data".into()),
+    };
+    let tx = trinci_core::Transaction::UnitTransaction(sign_tx.clone());
+    let message = rmp_serialize(&Message::PutTransactionRequest { confirm: true, tx })?;
+
+    Ok(Expected {
+        public_key: hex::encode(public_key),
+        data: hex::encode(sign_tx.data.serialize()),
+        hash: hex::encode(sign_tx.data.primary_hash().as_bytes()),
+        message: hex::encode(message),
+        signature: hex::encode(&sign_tx.signature),
+    })
+}
+
+/// Check a test vector, collecting an error message for every mismatch
+fn check_test_vector(vector: &TestVector) -> Result<Vec<String>> {
+    let expected = match &vector.expected {
+        Some(expected) => expected,
+        None => return Ok(vec![String::from("expected bytes not generated")]),
+    };
+    let mut errors = expected.compare(&derive_test_vector(vector)?);
+
+    // The checked-in signature must verify with the checked-in key and data
+    let public_key = rmp_deserialize::<PublicKey>(&hex::decode(&expected.public_key)?)?;
+    let data = hex::decode(&expected.data)?;
+    if !public_key.verify(&data, &hex::decode(&expected.signature)?) {
+        errors.push(String::from("signature: verification failed"));
+    }
+    Ok(errors)
+}
+
+pub fn selftest(input_args: Arguments) -> Result<()> {
+    let (path, generate) = match input_args {
+        Arguments::SelftestArgs { vectors, generate } => (vectors, generate),
+        _ => return Err("unexpected arguments".into()),
+    };
+    let mut test_vectors = match &path {
+        Some(path) => vectors::load(path)?,
+        None => vectors::from_json_str(vectors::DEFAULT_VECTORS)?,
+    };
+
+    let output = if generate {
+        for vector in test_vectors.iter_mut() {
+            vector.expected = Some(derive_test_vector(vector)?);
+        }
+        // `--generate` requires `--vectors`
+        vectors::save(path.as_deref().unwrap_or_default(), &test_vectors)?;
+        format!("OK|{} vectors generated", test_vectors.len())
+    } else {
+        let mut failures = vec![];
+        for vector in &test_vectors {
+            let errors = match check_test_vector(vector) {
+                Ok(errors) => errors,
+                Err(e) => vec![e.to_string()],
+            };
+            if !errors.is_empty() {
+                failures.push(format!("{}: {}", vector.name, errors.join(", ")));
+            }
+        }
+        if failures.is_empty() {
+            format!("OK|{} vectors", test_vectors.len())
+        } else {
+            format!("KO|{}", failures.join("; "))
+        }
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+/// Ask the transaction, show the preview and submit it once confirmed,
+/// waiting for its receipt
+fn wizard_tx<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    profile: &Profile,
+    url: &str,
+) -> Result<()> {
+    let abi = match &profile.abi {
+        Some(path) => Some(AbiRegistry::load(path)?),
+        None => None,
+    };
+    let (args, signer) = wizard::ask_unit_tx(prompter, profile, abi.as_ref())?;
+    let options = AppOptions {
+        abi,
+        policy: match &profile.policy {
+            Some(path) => Some(policy::SigningPolicy::load(path)?),
+            None => None,
+        },
+        audit_log: profile.audit_log.clone(),
+        signer,
+        fuel_history: profile.fuel_history.clone(),
+        ..Default::default()
+    };
+
+    let call = (args.contract.to_lowercase(), args.method.clone());
+    let sign_tx = create_signed_tx(args, &options)?;
+    verify_signed_tx(&sign_tx)?;
+    let returns_schema = match &options.abi {
+        Some(abi) => get_returns_schema(abi, &call)?,
+        None => None,
+    };
+
+    let preview = decode::signed_tx_to_json(&sign_tx);
+    prompter.say(&serde_json::to_string_pretty(&preview)?)?;
+    if !prompter.confirm("Submit the transaction")? {
+        audit(&options, &sign_tx.data, "discarded")?;
+        prompter.say("KO|Transaction discarded")?;
+        return Ok(());
+    }
+
+    let tx = trinci_core::Transaction::UnitTransaction(sign_tx.clone());
+    let buf = rmp_serialize(&Message::PutTransactionRequest { confirm: true, tx })?;
+    audit(&options, &sign_tx.data, "signed")?;
+    let res = send_tx(buf, url.to_string());
+    let outcome = match &res {
+        Ok(output) => output.clone(),
+        Err(e) => format!("KO|{}", e),
+    };
+    audit(&options, &sign_tx.data, &outcome)?;
+    prompter.say(&res?)?;
+    if outcome.starts_with("OK|") {
+        prompter.say("Waiting for the receipt...")?;
+        let rx = wait_receipt(url, sign_tx.data.primary_hash())?;
+        record_fuel(&options, &call, &rx)?;
+        let receipt = decode::receipt_to_json(&rx, returns_schema.as_ref());
+        prompter.say(&format!("OK|{}", receipt))?;
+    }
+    Ok(())
+}
+
+pub fn wizard(input_args: Arguments) -> Result<()> {
+    let profile = match input_args {
+        Arguments::InteractiveArgs { profile } => profile,
+        _ => return Err("unexpected arguments".into()),
+    };
+    let stdin = io::stdin();
+    let mut prompter = Prompter::new(stdin.lock(), io::stdout());
+
+    let profile = wizard::ask_profile(&mut prompter, profile)?;
+    let url = prompter.ask("Node url", profile.url.as_deref())?;
+    wizard_tx(&mut prompter, &profile, &url)
+}
+
+pub fn repl(input_args: Arguments) -> Result<()> {
+    let profile = match input_args {
+        Arguments::InteractiveArgs { profile } => profile,
+        _ => return Err("unexpected arguments".into()),
+    };
+    let stdin = io::stdin();
+    let mut prompter = Prompter::new(stdin.lock(), io::stdout());
+
+    let profile = wizard::ask_profile(&mut prompter, profile)?;
+    let url = prompter.ask("Node url", profile.url.as_deref())?;
+    let abi = match &profile.abi {
+        Some(path) => Some(AbiRegistry::load(path)?),
+        None => None,
+    };
+    prompter.say(REPL_HELP)?;
+
+    loop {
+        // The session ends with the input
+        let line = match prompter.ask_with("trinci", None, |line| Ok(line.to_string())) {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let res = match ReplCommand::parse(&line) {
+            _ if line.is_empty() => continue,
+            Ok(ReplCommand::Exit) => break,
+            Ok(ReplCommand::Help) => prompter.say(REPL_HELP),
+            Ok(ReplCommand::NodeInfo) => node_info(url.clone()),
+            Ok(ReplCommand::GetBlock { height, txs }) => {
+                get_block(Arguments::GetBlockArgs { height, txs }, url.clone())
+            }
+            Ok(ReplCommand::GetTx { hash }) => get_tx(Arguments::GetTxArgs { hash }, url.clone()),
+            Ok(ReplCommand::GetReceipt { hash }) => get_receipt(
+                Arguments::GetReceiptArgs { hash },
+                url.clone(),
+                AppOptions {
+                    abi: abi.clone(),
+                    fuel_history: profile.fuel_history.clone(),
+                    ..Default::default()
+                },
+            ),
+            Ok(ReplCommand::Tx) => wizard_tx(&mut prompter, &profile, &url),
+            Err(e) => Err(e.into()),
+        };
+        // The query subcommands print without the line end
+        match res {
+            Ok(()) => prompter.say("")?,
+            Err(e) => prompter.say(&format!("KO|{}", e))?,
+        }
+    }
+    Ok(())
+}
+
+/// Sign the transaction of the rows args, a bulk one for more rows
+fn sign_payout_tx(
+    mut txs_args: Vec<UnitTxArgs>,
+    options: &AppOptions,
+    signer: &dyn Signer,
+) -> Result<(Hash, trinci_core::Transaction)> {
+    if txs_args.len() == 1 {
+        let nonce = rand::random::<u64>().to_be_bytes().to_vec();
+        let sign_tx = sign_unit_tx(txs_args.remove(0), options, nonce, signer)?;
+        let hash = sign_tx.data.primary_hash();
+        Ok((hash, trinci_core::Transaction::UnitTransaction(sign_tx)))
+    } else {
+        let bulk_tx = create_bulk_tx(txs_args, options, signer)?;
+        let hash = bulk_tx.data.primary_hash();
+        Ok((hash, trinci_core::Transaction::BulkTransaction(bulk_tx)))
+    }
+}
+
+/// Look up a submitted transaction on the node
+fn find_tx(url: &str, hash: Hash) -> Result<bool> {
+    match request_message(url.to_string(), &Message::GetTransactionRequest { hash })? {
+        Message::GetTransactionResponse { .. } => Ok(true),
+        Message::Exception(e) if matches!(e.kind, ErrorKind::ResourceNotFound) => Ok(false),
+        Message::Exception(e) => Err(e.into()),
+        msg => Err(format!("unexpected message {:?}", msg).into()),
+    }
+}
+
+pub fn payout(input_args: Arguments, url: String, options: AppOptions) -> Result<()> {
+    let config = match input_args {
+        Arguments::PayoutArgs(config) => config,
+        _ => return Err("unexpected arguments".into()),
+    };
+    if config.bulk_size > 1 && matches!(options.signer, Some(SignerConfig::Remote { .. })) {
+        return Err("the remote signer signs only unit transactions, use --bulk-size 1".into());
+    }
+    let mut rows = payout::load(&config.rows)?;
+
+    // A submission without an answer is paid again only if the node does not know it
+    let unknown: BTreeSet<String> = rows
+        .iter()
+        .filter(|row| row.is_unknown() && !row.hash.is_empty())
+        .map(|row| row.hash.clone())
+        .collect();
+    for hash in unknown {
+        let outcome = match find_tx(&url, Hash::from_hex(&hash)?) {
+            Ok(true) => OUTCOME_PENDING.to_string(),
+            Ok(false) => String::from("KO|transaction not found"),
+            Err(e) => {
+                eprintln!("Error looking up the transaction {}: {}", hash, e);
+                continue;
+            }
+        };
+        for row in rows.iter_mut().filter(|row| row.hash == hash) {
+            row.outcome = outcome.clone();
+        }
+    }
+    let todo: Vec<usize> = (0..rows.len()).filter(|i| !rows[*i].is_settled()).collect();
+
+    // The args of every row are checked before the first payment
+    let mut txs_args = Vec::with_capacity(todo.len());
+    let mut errors = vec![];
+    for i in &todo {
+        let res = config
+            .tx
+            .unit_tx_args(&rows[*i], config.private_key.clone())
+            .and_then(|args| {
+                validate_unit_tx_args(&args, &options.fuel_bounds)?;
+                Ok(args)
+            });
+        match res {
+            Ok(args) => txs_args.push(args),
+            Err(e) => errors.push(format!("{}: {}", rows[*i].account, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("; ").into());
+    }
+
+    // A single signer for the run, not initialized if there is nothing to pay
+    let signer = if todo.is_empty() {
+        None
+    } else {
+        Some(create_signer(&config.private_key, &options)?)
+    };
+    let mut txs_args = txs_args.into_iter();
+    for (n, chunk) in todo.chunks(config.bulk_size).enumerate() {
+        if n > 0 {
+            std::thread::sleep(config.interval);
+        }
+        let chunk_args = txs_args.by_ref().take(chunk.len()).collect();
+        let signer = signer.as_deref().ok_or("missing signer")?;
+        let (hash, tx) = match sign_payout_tx(chunk_args, &options, signer) {
+            Ok(signed) => signed,
+            Err(e) => {
+                for i in chunk {
+                    rows[*i].hash = String::new();
+                    rows[*i].outcome = format!("KO|{}", e);
+                }
+                payout::save(&config.result, &rows)?;
+                continue;
+            }
+        };
+
+        // Every bulk root and node is recorded, with the outcome of the bulk transaction
+        let txs_data = signed_tx_data(&tx);
+        for tx_data in &txs_data {
+            audit(&options, tx_data, "signed")?;
+        }
+        let buf = rmp_serialize(&Message::PutTransactionRequest {
+            confirm: true,
+            tx: tx.clone(),
+        })?;
+        let (output, outcome) = match send_tx(buf, url.clone()) {
+            Ok(output) if output.starts_with("OK|") => (output, OUTCOME_PENDING.to_string()),
+            Ok(output) => (output.clone(), output),
+            // The node may have accepted it, it is looked up before paying again
+            Err(e) => (format!("KO|{}", e), format!("{}{}", OUTCOME_UNKNOWN, e)),
+        };
+        let hash = decode::hash_to_hex(&hash);
+        for i in chunk {
+            rows[*i].hash = hash.clone();
+            rows[*i].outcome = outcome.clone();
+        }
+        // Saved at every payment, a stopped run is resumed from the result
+        payout::save(&config.result, &rows)?;
+        for tx_data in &txs_data {
+            audit(&options, tx_data, &output)?;
+        }
+    }
+
+    // Also the rows of the previous runs still awaiting a receipt
+    let pending: BTreeSet<String> = rows
+        .iter()
+        .filter(|row| row.is_awaiting_receipt() && !row.hash.is_empty())
+        .map(|row| row.hash.clone())
+        .collect();
+    for hash in pending {
+        let outcome = match wait_receipt(&url, Hash::from_hex(&hash)?) {
+            Ok(rx) if rx.success => OUTCOME_SUCCESS.to_string(),
+            Ok(rx) => format!("failed: {}", String::from_utf8_lossy(&rx.returns)),
+            Err(e) => {
+                eprintln!("Error waiting the receipt of {}: {}", hash, e);
+                format!("{}{}", OUTCOME_RECEIPT_ERROR, e)
+            }
+        };
+        // Without a receipt an unanswered submission stays unknown
+        for row in rows
+            .iter_mut()
+            .filter(|row| row.hash == hash)
+            .filter(|row| !(row.is_unknown() && outcome.starts_with(OUTCOME_RECEIPT_ERROR)))
+        {
+            row.outcome = outcome.clone();
+        }
+    }
+    payout::save(&config.result, &rows)?;
+
+    let summary = payout::summary(&rows);
+    let output = if rows.iter().all(|row| row.outcome == OUTCOME_SUCCESS) {
+        format!("OK|{}", summary)
+    } else {
+        format!("KO|{}", summary)
+    };
+    io::stdout()
+        .write_all(output.as_bytes())
+        .unwrap_or_default();
+
+    Ok(())
+}
+
+pub fn mock_node(input_args: Arguments) -> Result<()> {
+    match input_args {
+        Arguments::MockNodeArgs { listen, scenario } => mock_node::serve(&listen, scenario),
+        _ => Err("unexpected arguments".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_node::{dummy_transaction, ExceptionKind, MockNode, Reply};
+    use crate::multisig::TxTemplate;
+    use crate::payout::{PayoutConfig, PayoutTx};
+    use proptest::prelude::*;
+    use serde_value::Value;
+    use trinci_core::crypto::HashAlgorithm;
+
+    fn put_request() -> Vec<u8> {
+        let message = Message::PutTransactionRequest {
+            confirm: true,
+            tx: dummy_transaction(),
+        };
+        rmp_serialize(&message).unwrap()
+    }
+
+    #[test]
+    fn send_tx_and_get_receipt() {
+        let node = MockNode::start(vec![Reply::Accept {
+            success: true,
+            returns: String::from("c3"),
+        }])
+        .unwrap();
+
+        let res = send_tx(put_request(), node.url()).unwrap();
+
+        let hash = match res.split_once('|') {
+            Some(("OK", hash)) => Hash::from_hex(hash).unwrap(),
+            _ => panic!("unexpected outcome {}", res),
+        };
+        match send_message(node.url(), &Message::GetReceiptRequest { hash }).unwrap() {
+            Message::GetReceiptResponse { rx } => {
+                assert!(rx.success);
+                assert_eq!(rx.returns, [0xc3]);
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn send_tx_scripted_replies() {
+        let node = MockNode::start(vec![
+            Reply::Exception {
+                kind: ExceptionKind::DuplicatedUnconfirmedTx,
+            },
+            Reply::True,
+            Reply::False,
+            Reply::HttpError {
+                status: 500,
+                body: String::from("internal error"),
+                accepted: false,
+            },
+        ])
+        .unwrap();
+
+        let res = send_tx(put_request(), node.url()).unwrap();
+        assert_eq!(res, "KO|DuplicatedUnconfirmedTx");
+        let res = send_tx(put_request(), node.url()).unwrap();
+        assert_eq!(res, "OK|Valid Transaction!");
+        let res = send_tx(put_request(), node.url()).unwrap();
+        assert_eq!(res, "KO|Invalid Transaction!");
+        let res = send_tx(put_request(), node.url()).unwrap_err();
+        assert_eq!(res.to_string(), "internal error");
+    }
+
+    /// Temporary file path, unique to the test and the process
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("trinci_sign_{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn multisig_proposal(signer: &str) -> Proposal {
+        let tx = TxTemplate {
+            target: String::from("#TREASURY"),
+            network: String::from("SKYNET"),
+            fuel: 1000,
+            contract: String::new(),
+            method: String::from("approve"),
+            args: Value::Bytes(vec![1, 2]),
+        };
+        Proposal::new(tx, vec![signer.to_string()]).unwrap()
+    }
+
+    fn multisig_approval(tx: &TxTemplate) -> (SignedTransaction, Approval) {
+        let sign_tx = create_signed_tx(
+            tx.to_unit_tx_args(test_private_key()),
+            &AppOptions::default(),
+        )
+        .unwrap();
+        (sign_tx.clone(), create_approval(sign_tx).unwrap())
+    }
+
+    #[test]
+    fn multisig_approval_checks() {
+        let signer = LocalSigner::new(&test_private_key())
+            .unwrap()
+            .public_key()
+            .unwrap()
+            .to_account_id();
+        let proposal = multisig_proposal(&signer);
+
+        let (sign_tx, approval) = multisig_approval(&proposal.tx);
+        assert!(check_approval(&proposal, &approval).is_ok());
+
+        // Another transaction
+        let mut tx = proposal.tx.clone();
+        tx.fuel = 2000;
+        tx.args = Value::Bytes(vec![3]);
+        let (_, other) = multisig_approval(&tx);
+        let err = check_approval(&proposal, &other).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "fuel differs from the proposal, args differs from the proposal"
+        );
+
+        // Listed under another signer
+        let mut other = approval.clone();
+        other.signer = String::from("#SIGNER2");
+        let err = check_approval(&proposal, &other).unwrap_err();
+        assert_eq!(err.to_string(), format!("signed by `{}`", signer));
+
+        // Tampered signature
+        let mut tampered = sign_tx;
+        tampered.signature[10] ^= 1;
+        let other = create_approval(tampered).unwrap();
+        let err = check_approval(&proposal, &other).unwrap_err();
+        assert_eq!(err.to_string(), "signature verification failed");
+    }
+
+    #[test]
+    fn multisig_submit_rejects_invalid_approvals() {
+        let signer = LocalSigner::new(&test_private_key())
+            .unwrap()
+            .public_key()
+            .unwrap()
+            .to_account_id();
+        let mut proposal = multisig_proposal(&signer);
+        let mut tx = proposal.tx.clone();
+        tx.target = String::from("#EVIL");
+        let (_, approval) = multisig_approval(&tx);
+        proposal.add_approval(approval).unwrap();
+        let path = temp_path("multisig_submit.json");
+        proposal.save(&path).unwrap();
+        let node = MockNode::start(vec![]).unwrap();
+
+        let res = multisig(
+            Arguments::MultisigArgs(MultisigCommand::Submit {
+                proposal: path.clone(),
+            }),
+            node.url(),
+            AppOptions::default(),
+        );
+
+        std::fs::remove_file(&path).unwrap_or_default();
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            format!("approval of `{}`: target differs from the proposal", signer)
+        );
+        assert_eq!(node.received(), 0);
+    }
+
+    #[test]
+    fn multisig_submit_goes_on_after_errors() {
+        let signer = LocalSigner::new(&test_private_key())
+            .unwrap()
+            .public_key()
+            .unwrap()
+            .to_account_id();
+        let mut proposal = multisig_proposal(&signer);
+        proposal.signers.push(String::from("#SIGNER2"));
+        let (_, approval) = multisig_approval(&proposal.tx);
+        proposal.add_approval(approval).unwrap();
+        let node = MockNode::start(vec![Reply::HttpError {
+            status: 500,
+            body: String::from("internal error"),
+            accepted: false,
+        }])
+        .unwrap();
+        let log = temp_path("multisig_audit.jsonl");
+        let options = AppOptions {
+            audit_log: Some(log.clone()),
+            ..Default::default()
+        };
+
+        let lines = submit_approvals(&proposal, &node.url(), &options).unwrap();
+
+        let text = std::fs::read_to_string(&log).unwrap();
+        std::fs::remove_file(&log).unwrap_or_default();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("KO|"));
+        assert!(lines[0].ends_with(&format!("|{}", signer)));
+        assert_eq!(lines[1], "KO|missing approvals: #SIGNER2");
+        let entry: audit::AuditEntry = serde_json::from_str(text.trim_end()).unwrap();
+        assert_eq!(format!("{}|{}", entry.outcome, signer), lines[0]);
+    }
+
+    #[test]
+    fn submit_unit_tx_audits_before_sending() {
+        let node = MockNode::start(vec![Reply::accept()]).unwrap();
+        let log = temp_path("submit_audit.jsonl");
+        let args = UnitTxArgs {
+            target: String::from("#MYASSET"),
+            network: String::from("SKYNET"),
+            fuel: 1000,
+            contract: String::new(),
+            method: String::from("transfer"),
+            args: Value::Bytes(vec![1, 2]),
+            private_key: test_private_key(),
+        };
+        let options = AppOptions {
+            audit_log: Some(log.clone()),
+            ..Default::default()
+        };
+
+        submit_unit_tx(Arguments::UnitTxArgsType(args), node.url(), options).unwrap();
+
+        let text = std::fs::read_to_string(&log).unwrap();
+        std::fs::remove_file(&log).unwrap_or_default();
+        let entries: Vec<audit::AuditEntry> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].outcome, "signed");
+        assert_eq!(entries[1].outcome, format!("OK|{}", entries[0].tx_hash));
+        assert_eq!(entries[1].tx_hash, entries[0].tx_hash);
+    }
+
+    #[test]
+    fn submit_unit_tx_batch_checks_every_row_first() {
+        let row = |fuel| UnitTxArgs {
+            target: String::from("#MYASSET"),
+            network: String::from("SKYNET"),
+            fuel,
+            contract: String::new(),
+            method: String::from("transfer"),
+            args: Value::Bytes(vec![1, 2]),
+            private_key: test_private_key(),
+        };
+        let node = MockNode::start(vec![]).unwrap();
+
+        let err = submit_unit_tx(
+            Arguments::UnitTxBatchArgs(vec![row(1000), row(0)]),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "row 2: fuel: 0 is out of bounds [1, 18446744073709551615]"
+        );
+        assert_eq!(node.received(), 0);
+
+        submit_unit_tx(
+            Arguments::UnitTxBatchArgs(vec![row(1000), row(2000)]),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(node.received(), 2);
+    }
+
+    #[test]
+    fn default_vectors_match_the_checked_in_bytes() {
+        for vector in vectors::from_json_str(vectors::DEFAULT_VECTORS).unwrap() {
+            let errors = check_test_vector(&vector).unwrap();
+
+            assert!(errors.is_empty(), "{}: {}", vector.name, errors.join("; "));
+        }
+    }
+
+    #[test]
+    fn test_vector_mismatch() {
+        let mut vector = vectors::from_json_str(vectors::DEFAULT_VECTORS)
+            .unwrap()
+            .remove(0);
+        vector.nonce = String::from("00000000000000ff");
+
+        assert!(!check_test_vector(&vector).unwrap().is_empty());
+    }
+
+    #[test]
+    fn get_receipt_not_found() {
+        let node = MockNode::start(vec![]).unwrap();
+        let hash = Hash::from_data(HashAlgorithm::Sha256, b"unknown");
+
+        assert!(send_message(node.url(), &Message::GetReceiptRequest { hash }).is_err());
+    }
+
+    #[test]
+    fn get_receipt_records_fuel() {
+        let node = MockNode::start(vec![]).unwrap();
+        let res = send_tx(put_request(), node.url()).unwrap();
+        let hash = res.strip_prefix("OK|").unwrap().to_string();
+        let path = temp_path("get_receipt_fuel.jsonl");
+        std::fs::remove_file(&path).unwrap_or_default();
+        let options = AppOptions {
+            fuel_history: Some(path.clone()),
+            ..Default::default()
+        };
+
+        get_receipt(Arguments::GetReceiptArgs { hash }, node.url(), options).unwrap();
+
+        let history = fuel::load_history(&path).unwrap();
+        std::fs::remove_file(&path).unwrap_or_default();
+        assert_eq!(
+            history,
+            vec![FuelRecord {
+                contract: String::new(),
+                method: String::from("transfer"),
+                burned_fuel: 0,
+                success: true,
+            }]
+        );
+    }
+
+    /// Contract args with every type the formats must carry
+    fn arb_args() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            any::<bool>().prop_map(Value::Bool),
+            any::<u64>().prop_map(Value::U64),
+            any::<i64>().prop_map(Value::I64),
+            "[ -~]{0,16}".prop_map(Value::String),
+            prop::collection::vec(any::<u8>(), 0..32).prop_map(Value::Bytes),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Seq),
+                // No `$` in the keys, a single `$` key map is a typed hint
+                prop::collection::btree_map(
+                    "[a-z]{1,8}".prop_map(Value::String),
+                    inner.clone(),
+                    0..8
+                )
+                .prop_map(Value::Map),
+                prop::collection::btree_map(any::<u64>().prop_map(Value::U64), inner, 1..4)
+                    .prop_map(Value::Map),
+            ]
+        })
+    }
+
+    fn arb_unit_tx_args() -> impl Strategy<Value = UnitTxArgs> {
+        let contract = prop_oneof![
+            Just(String::new()),
+            any::<[u8; 32]>().prop_map(|digest| format!("1220{}", hex::encode(digest))),
+        ];
+        (
+            "#[A-Za-z0-9]{1,16}",
+            "[A-Za-z]{1,8}",
+            1..=u64::MAX,
+            contract,
+            "[a-z_]{1,16}",
+            arb_args(),
+        )
+            .prop_map(
+                |(target, network, fuel, contract, method, args)| UnitTxArgs {
+                    target,
+                    network,
+                    fuel,
+                    contract,
+                    method,
+                    args,
+                    private_key: test_private_key(),
+                },
+            )
+    }
+
+    fn test_private_key() -> PrivateKey {
+        vectors::from_json_str(vectors::DEFAULT_VECTORS).unwrap()[0]
+            .args
+            .private_key
+            .clone()
+    }
+
+    /// The args are compared by their encoding, the formats do not keep the integers width
+    fn assert_same_args(res: &UnitTxArgs, expected: &UnitTxArgs) {
+        assert_eq!(res.target, expected.target);
+        assert_eq!(res.network, expected.network);
+        assert_eq!(res.fuel, expected.fuel);
+        assert_eq!(res.contract, expected.contract);
+        assert_eq!(res.method, expected.method);
+        assert_eq!(
+            encode::encode_args(&res.args).unwrap(),
+            encode::encode_args(&expected.args).unwrap()
+        );
+        assert!(res.private_key == expected.private_key);
+    }
+
+    proptest! {
+        #[test]
+        fn unit_tx_args_formats_round_trip(args in arb_unit_tx_args()) {
+            let buf = args.to_msgpack().unwrap();
+
+            let res = UnitTxArgs::from_hex_string(&hex::encode(&buf)).unwrap();
+            assert_same_args(&res, &args);
+            let res = UnitTxArgs::from_bs58_string(&bs58::encode(&buf).into_string()).unwrap();
+            assert_same_args(&res, &args);
+
+            // The json args carry the binary and the non-string keys maps as typed hints
+            let buf = encode::encode_args(&args.args).unwrap();
+            let (json_args, _) = decode::msgpack_to_json(&buf).unwrap();
+            let mut json = serde_json::to_value(&args).unwrap();
+            json["args"] = json_args;
+            let res = UnitTxArgs::from_json_string(&json.to_string()).unwrap();
+            assert_same_args(&res, &args);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn create_unit_tx_decodes_back(args in arb_unit_tx_args()) {
+            let caller = LocalSigner::new(&args.private_key).unwrap().public_key().unwrap();
+
+            let (_, buf) = create_unit_tx_message(
+                Arguments::UnitTxArgsType(args.clone()),
+                &AppOptions::default(),
+            )
+            .unwrap();
+
+            let sign_tx = match rmp_deserialize::<Message>(&buf).unwrap() {
+                Message::PutTransactionRequest {
+                    tx: trinci_core::Transaction::UnitTransaction(sign_tx),
+                    ..
+                } => sign_tx,
+                msg => panic!("unexpected message {:?}", msg),
+            };
+            verify_signed_tx(&sign_tx).unwrap();
+            let data = match sign_tx.data {
+                TransactionData::V1(data) => data,
+                data => panic!("unexpected transaction data {:?}", data),
+            };
+            assert_eq!(data.account, args.target);
+            assert_eq!(data.network, args.network);
+            assert_eq!(data.fuel_limit, args.fuel);
+            let contract = data.contract.as_ref().map(decode::hash_to_hex);
+            assert_eq!(contract.unwrap_or_default(), args.contract);
+            assert_eq!(data.method, args.method);
+            assert_eq!(data.args, encode::encode_args(&args.args).unwrap());
+            assert_eq!(data.caller, caller);
+        }
+    }
+
+    #[test]
+    fn wizard_tx_submit_and_wait_receipt() {
+        let node = MockNode::start(vec![Reply::Accept {
+            success: true,
+            returns: String::from("c3"),
+        }])
+        .unwrap();
+        let vectors: serde_json::Value = serde_json::from_str(vectors::DEFAULT_VECTORS).unwrap();
+        let key_file = temp_path("wizard_tx.key");
+        std::fs::write(
+            &key_file,
+            vectors[0]["args"]["private_key"].as_str().unwrap(),
+        )
+        .unwrap();
+        let profile = Profile {
+            network: Some(String::from("SKYNET")),
+            private_key_file: Some(key_file.clone()),
+            ..Default::default()
+        };
+        // Signer config, key file, target, network, fuel, contract, method, args, confirm
+        let answers = "\n\n#ANYACCOUNT\n\n1000\n\ntransfer\n{\"units\":100}\ny\n";
+        let mut output = vec![];
+        let mut prompter = Prompter::new(io::Cursor::new(answers.as_bytes()), &mut output);
+
+        wizard_tx(&mut prompter, &profile, &node.url()).unwrap();
+
+        std::fs::remove_file(&key_file).unwrap_or_default();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("\"method\": \"transfer\""));
+        assert!(output.contains("\"success\":true"));
+        assert_eq!(node.received(), 2);
+    }
+
+    fn payout_config(rows: &str, result: &str, bulk_size: usize) -> PayoutConfig {
+        PayoutConfig {
+            rows: rows.to_string(),
+            result: result.to_string(),
+            tx: PayoutTx::Transfer {
+                asset: String::from("#MYASSET"),
+                network: String::from("SKYNET"),
+                fuel: 1000,
+                contract: String::new(),
+                from: String::from("#TREASURY"),
+            },
+            private_key: test_private_key(),
+            bulk_size,
+            interval: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn payout_bulk_audit_entries() {
+        let rows = temp_path("payout_audit.csv");
+        let result = temp_path("payout_audit_result.csv");
+        let log = temp_path("payout_audit.jsonl");
+        std::fs::write(&rows, "account,units,memo\n#ALICE,100,\n#BOB,50,\n").unwrap();
+        let node = MockNode::start(vec![Reply::accept()]).unwrap();
+        let options = AppOptions {
+            audit_log: Some(log.clone()),
+            ..Default::default()
+        };
+
+        payout(
+            Arguments::PayoutArgs(payout_config(&rows, &result, 2)),
+            node.url(),
+            options,
+        )
+        .unwrap();
+
+        let text = std::fs::read_to_string(&log).unwrap();
+        let res = payout::load(&result).unwrap();
+        for path in [&rows, &result, &log] {
+            std::fs::remove_file(path).unwrap_or_default();
+        }
+        assert_eq!(audit::verify(&text, None).unwrap().count, 4);
+        let entries: Vec<audit::AuditEntry> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let outcomes: Vec<&str> = entries.iter().map(|entry| entry.outcome.as_str()).collect();
+        let submitted = format!("OK|{}", res[0].hash);
+        assert_eq!(outcomes, ["signed", "signed", &submitted, &submitted]);
+        // The root and the node
+        assert_ne!(entries[0].tx_hash, entries[1].tx_hash);
+    }
+
+    #[test]
+    fn payout_records_receipt_errors() {
+        let result = temp_path("payout_receipt_result.csv");
+        std::fs::write(
+            &result,
+            "account,units,memo,hash,outcome\n#ALICE,100,,\
+             12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118,pending\n",
+        )
+        .unwrap();
+
+        // Nothing to pay, the receipt request fails
+        payout(
+            Arguments::PayoutArgs(payout_config(&result, &result, 1)),
+            String::from("http://127.0.0.1:1"),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let res = payout::load(&result).unwrap();
+        std::fs::remove_file(&result).unwrap_or_default();
+        assert!(res[0].outcome.starts_with(OUTCOME_RECEIPT_ERROR));
+        // Possibly paid, its receipt is awaited again by the next run
+        assert!(res[0].is_settled());
+        assert!(res[0].is_awaiting_receipt());
+    }
+
+    #[test]
+    fn payout_and_retry_failed_rows() {
+        let rows = temp_path("payout.csv");
+        let result = temp_path("payout_result.csv");
+        std::fs::write(
+            &rows,
+            "account,units,memo\n#ALICE,100,May\n#BOB,50,\n#CAROL,70,\n",
+        )
+        .unwrap();
+        let node = MockNode::start(vec![
+            Reply::accept(),
+            Reply::Exception {
+                kind: ExceptionKind::DuplicatedUnconfirmedTx,
+            },
+            Reply::Accept {
+                success: false,
+                returns: hex::encode("no funds"),
+            },
+        ])
+        .unwrap();
+
+        payout(
+            Arguments::PayoutArgs(payout_config(&rows, &result, 1)),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let res = payout::load(&result).unwrap();
+        let outcomes: Vec<&str> = res.iter().map(|row| row.outcome.as_str()).collect();
+        assert_eq!(
+            outcomes,
+            ["success", "KO|DuplicatedUnconfirmedTx", "failed: no funds"]
+        );
+        assert!(!res[0].hash.is_empty());
+
+        // The result is the input of the retry, only the unpaid rows are sent
+        let node = MockNode::start(vec![]).unwrap();
+        payout(
+            Arguments::PayoutArgs(payout_config(&result, &result, 2)),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let retried = payout::load(&result).unwrap();
+        std::fs::remove_file(&rows).unwrap_or_default();
+        std::fs::remove_file(&result).unwrap_or_default();
+        assert!(retried.iter().all(|row| row.outcome == OUTCOME_SUCCESS));
+        assert_eq!(retried[0].hash, res[0].hash);
+        // One bulk transaction with both rows, and its receipt request
+        assert_eq!(retried[1].hash, retried[2].hash);
+        assert_eq!(node.received(), 2);
+    }
+
+    #[test]
+    fn payout_lost_answer_not_paid_twice() {
+        let rows = temp_path("payout_lost.csv");
+        let result = temp_path("payout_lost_result.csv");
+        std::fs::write(&rows, "account,units,memo\n#ALICE,100,\n").unwrap();
+        // Executed, but the answer is lost
+        let node = MockNode::start(vec![Reply::HttpError {
+            status: 504,
+            body: String::from("timeout"),
+            accepted: true,
+        }])
+        .unwrap();
+
+        payout(
+            Arguments::PayoutArgs(payout_config(&rows, &result, 1)),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let res = payout::load(&result).unwrap();
+        assert_eq!(res[0].outcome, OUTCOME_SUCCESS);
+        assert!(!res[0].hash.is_empty());
+        assert_eq!(node.received(), 2);
+
+        // A run stopped before the receipts, the transaction is looked up
+        let mut stopped = res.clone();
+        stopped[0].outcome = format!("{}timeout", OUTCOME_UNKNOWN);
+        payout::save(&result, &stopped).unwrap();
+        payout(
+            Arguments::PayoutArgs(payout_config(&result, &result, 1)),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let retried = payout::load(&result).unwrap();
+        std::fs::remove_file(&rows).unwrap_or_default();
+        std::fs::remove_file(&result).unwrap_or_default();
+        assert_eq!(retried, res);
+        // The transaction and receipt requests, not a second payment
+        assert_eq!(node.received(), 4);
+    }
+
+    #[test]
+    fn payout_unknown_tx_paid_again() {
+        let result = temp_path("payout_unknown_result.csv");
+        let hash = "12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118";
+        std::fs::write(
+            &result,
+            format!(
+                "account,units,memo,hash,outcome\n#ALICE,100,,{},unknown: timeout\n",
+                hash
+            ),
+        )
+        .unwrap();
+        let node = MockNode::start(vec![]).unwrap();
+
+        payout(
+            Arguments::PayoutArgs(payout_config(&result, &result, 1)),
+            node.url(),
+            AppOptions::default(),
+        )
+        .unwrap();
+
+        let res = payout::load(&result).unwrap();
+        std::fs::remove_file(&result).unwrap_or_default();
+        assert_eq!(res[0].outcome, OUTCOME_SUCCESS);
+        assert_ne!(res[0].hash, hash);
+        // The lookup, the payment and its receipt request
+        assert_eq!(node.received(), 3);
+    }
+
+    #[test]
+    fn payout_bulk_with_remote_signer() {
+        let options = AppOptions {
+            signer: Some(SignerConfig::Remote {
+                url: String::from("http://127.0.0.1:1"),
+                alias: String::from("treasury"),
+                token_env: String::from("TRINCI_SIGNER_TOKEN"),
+            }),
+            ..Default::default()
+        };
+
+        let res = payout(
+            Arguments::PayoutArgs(payout_config("rows.csv", "result.csv", 2)),
+            String::from("http://127.0.0.1:1"),
+            options,
+        );
+
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "the remote signer signs only unit transactions, use --bulk-size 1"
+        );
+    }
+}
@@ -1,31 +1,390 @@
-use std::str::FromStr;
+use std::{
+    io::{self, Read},
+    str::FromStr,
+    time::Duration,
+};
 
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgGroup, ArgMatches, Command};
 use serde_json::Value;
+use zeroize::Zeroizing;
 
-use crate::types::{AppCommand, AppOperation, Arguments, Result, UnitTxArgs};
+use trinci_sign::{
+    abi::AbiRegistry,
+    asset::AssetOperation,
+    audit::Checkpoint,
+    fuel::{FuelSetting, DEFAULT_FUEL_MARGIN},
+    mock_node::load_scenario,
+    multisig::{MultisigCommand, TxTemplate},
+    payout::{PayoutConfig, PayoutTx},
+    policy::SigningPolicy,
+    remote_signer::{ServerConfig, REMOTE_SIGNER_TOKEN_ENV},
+    secret::PrivateKey,
+    signer::{SignerConfig, PKCS11_PIN_ENV},
+    template::{parse_set, rows_from_csv, Template, Vars},
+    types::{AppCommand, AppOperation, AppOptions, Arguments, Encoding, Result, UnitTxArgs},
+    validation::FuelBounds,
+    wizard::Profile,
+};
 
 /// Convert a base58 string into a vec
 pub fn bs58_into_vec(bs58_text: &str) -> Result<Vec<u8>> {
     bs58::decode(bs58_text).into_vec().map_err(|e| e.into())
 }
 
+fn policy_arg() -> Arg<'static> {
+    Arg::new("policy")
+        .long("policy")
+        .help("Signing policy file restricting what the key may sign")
+        .value_name("PATH")
+}
+
+fn audit_log_arg() -> Arg<'static> {
+    Arg::new("audit-log")
+        .long("audit-log")
+        .help("Audit log file where the produced signature is recorded")
+        .value_name("PATH")
+}
+
+fn fuel_history_arg() -> Arg<'static> {
+    Arg::new("fuel-history")
+        .long("fuel-history")
+        .help("History file of the fuel burned by the executed transactions")
+        .value_name("PATH")
+}
+
+fn fuel_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("fuel")
+            .long("fuel")
+            .help(
+                "Fuel limit in place of the args one, `auto` to estimate it from the --fuel-history only \
+                 (no dry run), the --fuel-cap or the args fuel for a method without history",
+            )
+            .value_name("FUEL|auto")
+            .requires_if("auto", "fuel-history"),
+        Arg::new("fuel-margin")
+            .long("fuel-margin")
+            .help("Safety margin of the automatic fuel limit, percent of the burned fuel [default: 20]")
+            .value_name("PERCENT")
+            .requires("fuel"),
+        Arg::new("fuel-cap")
+            .long("fuel-cap")
+            .help("Highest automatic fuel limit, the args fuel if not given")
+            .value_name("FUEL")
+            .requires("fuel"),
+        fuel_history_arg(),
+    ]
+}
+
+fn signer_args() -> Vec<Arg<'static>> {
+    vec![
+        Arg::new("signer-config")
+            .long("signer-config")
+            .help("Signer backend config file, in place of the private key")
+            .value_name("PATH"),
+        Arg::new("pkcs11-module")
+            .long("pkcs11-module")
+            .help("PKCS#11 module library holding the key, in place of the private key")
+            .value_name("PATH")
+            .conflicts_with("signer-config")
+            .requires("pkcs11-key"),
+        Arg::new("pkcs11-token")
+            .long("pkcs11-token")
+            .help("PKCS#11 token label, the first token if not given")
+            .value_name("LABEL")
+            .requires("pkcs11-module"),
+        Arg::new("pkcs11-key")
+            .long("pkcs11-key")
+            .help("PKCS#11 key label")
+            .value_name("LABEL")
+            .requires("pkcs11-module"),
+        Arg::new("remote-signer")
+            .long("remote-signer")
+            .help("Remote signer server url, in place of the private key")
+            .value_name("URL")
+            .conflicts_with_all(&["signer-config", "pkcs11-module"])
+            .requires("remote-alias"),
+        Arg::new("remote-alias")
+            .long("remote-alias")
+            .help("Alias of the key on the remote signer server")
+            .value_name("ALIAS")
+            .requires("remote-signer"),
+    ]
+}
+
+fn payout_command() -> Command<'static> {
+    let transfer_arg = |name: &'static str, help: &'static str, value_name: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .help(help)
+            .value_name(value_name)
+            .required_unless_present("template")
+    };
+
+    Command::new("payout")
+        .about("Pay the recipients of a CSV with asset transfers")
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .help("CSV with the `account`, `units` and optional `memo` columns, or a previous result")
+                .value_name("PATH")
+                .required(true),
+        )
+        .arg(
+            Arg::new("result")
+                .long("result")
+                .help("Result CSV with the hash and the outcome of every row, may be the input one")
+                .value_name("PATH")
+                .required(true),
+        )
+        .arg(transfer_arg("asset", "Asset account (the transactions target)", "ACCOUNT"))
+        .arg(transfer_arg("network", "Blockchain network", "NETWORK"))
+        .arg(transfer_arg("fuel", "Max fuel allowed for every transaction", "FUEL"))
+        .arg(transfer_arg("from", "Account paying the units", "ACCOUNT"))
+        .arg(
+            Arg::new("contract")
+                .long("contract")
+                .help("Multihash of the asset contract, the account one if not specified")
+                .value_name("HASH"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .help("Unit tx args template, with the `account`, `units` and `memo` placeholders, in place of the transfer")
+                .value_name("PATH")
+                .conflicts_with_all(&["asset", "network", "fuel", "from", "contract"]),
+        )
+        .arg(
+            Arg::new("private-key")
+                .long("private-key")
+                .help("Base58 of the private key bytes array in pkcs8 (`-` to read from stdin)")
+                .value_name("BASE58")
+                .required_unless_present_any(&["signer-config", "pkcs11-module", "remote-signer"]),
+        )
+        .arg(policy_arg())
+        .arg(audit_log_arg())
+        .args(signer_args())
+        .arg(
+            Arg::new("bulk-size")
+                .long("bulk-size")
+                .help("Rows packed in a bulk transaction, 1 for a unit transaction per row")
+                .value_name("ROWS")
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .help("Milliseconds between two submissions")
+                .value_name("MS")
+                .default_value("1000"),
+        )
+}
+
+fn asset_command(name: &'static str, about: &'static str) -> Command<'static> {
+    let account_arg = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .help(help)
+            .value_name("ACCOUNT")
+            .required(true)
+    };
+
+    let command = Command::new(name)
+        .about(about)
+        .arg(
+            Arg::new("asset")
+                .long("asset")
+                .help("Asset account (the transaction target)")
+                .value_name("ACCOUNT")
+                .required(true),
+        )
+        .arg(
+            Arg::new("network")
+                .long("network")
+                .help("Blockchain network")
+                .value_name("NETWORK")
+                .required(true),
+        )
+        .arg(
+            Arg::new("fuel")
+                .long("fuel")
+                .help("Max fuel allowed")
+                .value_name("FUEL")
+                .required(true),
+        )
+        .arg(
+            Arg::new("contract")
+                .long("contract")
+                .help("Multihash of the asset contract, the account one if not specified")
+                .value_name("HASH"),
+        )
+        .arg(
+            Arg::new("private-key")
+                .long("private-key")
+                .help("Base58 of the private key bytes array in pkcs8 (`-` to read from stdin)")
+                .value_name("BASE58")
+                .required_unless_present_any(&["signer-config", "pkcs11-module", "remote-signer"]),
+        )
+        .arg(policy_arg())
+        .arg(audit_log_arg())
+        .args(signer_args())
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .help("Trinci Node url, if given the transaction is submitted")
+                .value_name("URL"),
+        )
+        .arg(
+            Arg::new("encoding")
+                .long("encoding")
+                .help("Encoding of the output when the transaction is not submitted")
+                .value_name("ENCODING")
+                .possible_values(Encoding::VARIANTS)
+                .default_value("raw"),
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .help("File where the output is written in place of the stdout")
+                .value_name("PATH"),
+        );
+
+    let units_arg = Arg::new("units")
+        .long("units")
+        .help("Asset units")
+        .value_name("UNITS")
+        .required(true);
+
+    match name {
+        "transfer" => command
+            .arg(account_arg("from", "Sender account"))
+            .arg(account_arg("to", "Receiver account"))
+            .arg(units_arg),
+        "mint" => command
+            .arg(account_arg("to", "Receiver account"))
+            .arg(units_arg),
+        "burn" => command
+            .arg(account_arg("from", "Account from which units are burned"))
+            .arg(units_arg),
+        _ => command,
+    }
+}
+
 fn create_app() -> Command<'static> {
     let hex_arg = Arg::new("hex")
         .long("hex")
-        .help("Arguments in messagepacked HEX")
+        .help("Arguments in messagepacked HEX (`-` to read from stdin)")
         .value_name("HEX");
 
     let json_arg = Arg::new("json")
         .long("json")
-        .help("Arguments in json String")
+        .help("Arguments in json String (`-` to read from stdin)")
         .value_name("JSON");
 
     let bs58_arg = Arg::new("bs58")
         .long("bs58")
-        .help("Arguments in messagepacked base58")
+        .help("Arguments in messagepacked base58 (`-` to read from stdin)")
         .value_name("BASE58");
 
+    let json_file_arg = Arg::new("json-file")
+        .long("json-file")
+        .help("File with the arguments in json (`-` to read from stdin)")
+        .value_name("PATH");
+
+    let msgpack_file_arg = Arg::new("msgpack-file")
+        .long("msgpack-file")
+        .help("File with the arguments in binary messagepack (`-` to read from stdin)")
+        .value_name("PATH");
+
+    let unit_tx_input_group = ArgGroup::new("input")
+        .args(&[
+            "hex",
+            "bs58",
+            "json",
+            "json-file",
+            "msgpack-file",
+            "template",
+        ])
+        .required(true);
+
+    let template_args = [
+        Arg::new("template")
+            .long("template")
+            .help("Unit tx args template file, with `{{name}}` or `{{name:type}}` placeholders")
+            .value_name("PATH"),
+        Arg::new("set")
+            .long("set")
+            .help("Value of a template placeholder")
+            .value_name("NAME=VALUE")
+            .multiple_occurrences(true)
+            .requires("template"),
+        Arg::new("private-key")
+            .long("private-key")
+            .help("Base58 of the private key bytes array in pkcs8 for the template (`-` to read from stdin)")
+            .value_name("BASE58")
+            .requires("template"),
+        Arg::new("rows")
+            .long("rows")
+            .help("CSV file filling the template with every row, a transaction per row (`-` to read from stdin)")
+            .value_name("PATH")
+            .requires("template")
+            .conflicts_with("args-file"),
+    ];
+
+    let args_file_arg = Arg::new("args-file")
+        .long("args-file")
+        .help("File embedded as messagepack binary in place of the contract args (`-` to read from stdin)")
+        .value_name("PATH");
+
+    let url_arg = Arg::new("url")
+        .long("url")
+        .short('u')
+        .help("Trinci Node url")
+        .value_name("URL");
+
+    let lenient_arg = Arg::new("lenient")
+        .long("lenient")
+        .help("Skip the args validation, an invalid contract is ignored")
+        .conflicts_with_all(&["min-fuel", "max-fuel"]);
+
+    let min_fuel_arg = Arg::new("min-fuel")
+        .long("min-fuel")
+        .help("Minimum fuel allowed")
+        .value_name("FUEL");
+
+    let max_fuel_arg = Arg::new("max-fuel")
+        .long("max-fuel")
+        .help("Maximum fuel allowed")
+        .value_name("FUEL");
+
+    let abi_arg = Arg::new("abi")
+        .long("abi")
+        .help("Contracts interface descriptors file")
+        .value_name("PATH");
+
+    let proposal_arg = Arg::new("proposal")
+        .long("proposal")
+        .help("Multi-signature proposal file")
+        .value_name("PATH")
+        .required(true);
+
+    let profile_arg = Arg::new("profile")
+        .long("profile")
+        .help("Profile file with the defaults of the prompts, asked if not given")
+        .value_name("PATH");
+
+    let encoding_arg = Arg::new("encoding")
+        .long("encoding")
+        .help("Encoding of the output")
+        .value_name("ENCODING")
+        .possible_values(Encoding::VARIANTS);
+
+    let out_arg = Arg::new("out")
+        .long("out")
+        .help("File where the output is written in place of the stdout")
+        .value_name("PATH");
+
     Command::new("Trinci Blockchain Transaction Sign")
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
@@ -35,203 +394,1901 @@ fn create_app() -> Command<'static> {
         .subcommand(
             Command::new("create_unit_tx")
                 .about("Create a binary Trinci unit tx")
+                .arg(hex_arg.clone())
+                .arg(bs58_arg.clone())
+                .arg(json_arg.clone())
+                .arg(json_file_arg.clone())
+                .arg(msgpack_file_arg.clone())
+                .args(template_args.clone())
+                .group(unit_tx_input_group.clone())
+                .arg(args_file_arg.clone())
+                .arg(lenient_arg.clone())
+                .arg(abi_arg.clone())
+                .arg(policy_arg())
+                .arg(audit_log_arg())
+                .args(signer_args())
+                .arg(min_fuel_arg.clone())
+                .arg(max_fuel_arg.clone())
+                .args(fuel_args())
                 .arg(
-                    hex_arg
+                    encoding_arg
                         .clone()
-                        .required_unless_present_any(&["json", "bs58"])
-                        .conflicts_with_all(&["json", "bs58"]),
+                        .default_value_if("rows", None, Some("hex"))
+                        .default_value("raw"),
                 )
-                .arg(
-                    bs58_arg
-                        .clone()
-                        .required_unless_present_any(&["json", "hex"])
-                        .conflicts_with_all(&["json", "hex"]),
+                .arg(out_arg.clone()),
+        )
+        .subcommand(
+            Command::new("encode_args")
+                .about("Encode json unit tx args into the messagepack of the --hex and --bs58 inputs")
+                .arg(json_arg.clone())
+                .arg(json_file_arg.clone())
+                .group(
+                    ArgGroup::new("input")
+                        .args(&["json", "json-file"])
+                        .required(true),
                 )
+                .arg(lenient_arg.clone())
+                .arg(min_fuel_arg.clone())
+                .arg(max_fuel_arg.clone())
                 .arg(
-                    json_arg
-                        .clone()
-                        .required_unless_present_any(&["hex", "bs58"])
-                        .conflicts_with_all(&["hex", "bs58"]),
+                    Arg::new("encoding")
+                        .long("encoding")
+                        .help("Encoding of the output")
+                        .value_name("ENCODING")
+                        .possible_values(["hex", "bs58"])
+                        .default_value("hex"),
+                )
+                .arg(out_arg.clone()),
+        )
+        .subcommand(
+            Command::new("decode_args")
+                .about("Decode messagepacked unit tx args into json")
+                .arg(hex_arg.clone())
+                .arg(bs58_arg.clone())
+                .arg(msgpack_file_arg.clone())
+                .group(
+                    ArgGroup::new("input")
+                        .args(&["hex", "bs58", "msgpack-file"])
+                        .required(true),
                 ),
         )
         .subcommand(
             Command::new("submit_unit_tx")
                 .about("Submit to the Trinci Blockchain a unit tx")
+                .arg(hex_arg)
+                .arg(bs58_arg)
+                .arg(json_arg.clone())
+                .arg(json_file_arg.clone())
+                .arg(msgpack_file_arg)
+                .args(template_args)
+                .group(unit_tx_input_group)
+                .arg(args_file_arg)
+                .arg(url_arg.clone().required_unless_present("dry-run"))
+                .arg(lenient_arg.conflicts_with("dry-run"))
+                .arg(abi_arg.clone())
+                .arg(policy_arg())
+                .arg(audit_log_arg())
+                .args(signer_args())
+                .arg(min_fuel_arg)
+                .arg(max_fuel_arg)
+                .args(fuel_args())
                 .arg(
-                    hex_arg
-                        .clone()
-                        .required_unless_present_any(&["json", "bs58"]),
+                    Arg::new("check-network")
+                        .long("check-network")
+                        .help("Check that the args network matches the node network before sending"),
                 )
                 .arg(
-                    bs58_arg
-                        .clone()
-                        .required_unless_present_any(&["json", "hex"]),
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Validate and sign the transaction without sending it")
+                        .conflicts_with_all(&["check-network", "rows"]),
+                ),
+        )
+        .subcommand(
+            Command::new("to_message_pack")
+                .about("Convert a string or a json into a byte array (returned as string)")
+                .arg(json_arg)
+                .arg(json_file_arg)
+                .arg(
+                    Arg::new("string")
+                        .long("string")
+                        .help("String to convert in MessagePack (`-` to read from stdin)")
+                        .value_name("STRING"),
+                )
+                .group(
+                    ArgGroup::new("input")
+                        .args(&["json", "json-file", "string"])
+                        .required(true),
                 )
+                .arg(encoding_arg.default_value("bytes"))
+                .arg(out_arg),
+        )
+        .subcommand(
+            Command::new("from_message_pack")
+                .about("Convert a MessagePack byte array into json")
                 .arg(
-                    json_arg
-                        .clone()
-                        .required_unless_present_any(&["hex", "bs58"]),
+                    Arg::new("hex")
+                        .long("hex")
+                        .help("MessagePack in HEX (`-` to read from stdin)")
+                        .value_name("HEX"),
                 )
                 .arg(
-                    Arg::new("url")
-                        .long("url")
-                        .short('u')
-                        .help("Trinci Node url")
-                        .value_name("URL")
+                    Arg::new("bs58")
+                        .long("bs58")
+                        .help("MessagePack in base58 (`-` to read from stdin)")
+                        .value_name("BASE58"),
+                )
+                .arg(
+                    Arg::new("base64")
+                        .long("base64")
+                        .help("MessagePack in base64 (`-` to read from stdin)")
+                        .value_name("BASE64"),
+                )
+                .arg(
+                    Arg::new("bytes")
+                        .long("bytes")
+                        .help("MessagePack as byte array, eg: `[145,1]` (`-` to read from stdin)")
+                        .value_name("BYTES"),
+                )
+                .arg(
+                    Arg::new("file")
+                        .long("file")
+                        .help("File with the binary MessagePack (`-` to read from stdin)")
+                        .value_name("PATH"),
+                )
+                .group(
+                    ArgGroup::new("input")
+                        .args(&["hex", "bs58", "base64", "bytes", "file"])
                         .required(true),
                 ),
         )
         .subcommand(
-            Command::new("to_message_pack")
-                .about("Convert a string or a json into a byte array (returned as string)")
-                .arg(json_arg.clone().required_unless_present_any(&["string"]))
+            Command::new("asset")
+                .about("Build standard TRINCI asset transactions")
+                .subcommand_required(true)
+                .subcommand(asset_command("transfer", "Transfer units between accounts"))
+                .subcommand(asset_command("mint", "Mint units to an account"))
+                .subcommand(asset_command("burn", "Burn units of an account"))
+                .subcommand(asset_command("balance", "Balance of the caller account")),
+        )
+        .subcommand(
+            Command::new("multisig")
+                .about("Multi-signature approval flow for shared accounts")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("propose")
+                        .about("Create a proposal file with the approval tx to sign")
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .help("Approval tx args in json String, without private key (`-` to read from stdin)")
+                                .value_name("JSON"),
+                        )
+                        .arg(
+                            Arg::new("json-file")
+                                .long("json-file")
+                                .help("File with the approval tx args in json (`-` to read from stdin)")
+                                .value_name("PATH"),
+                        )
+                        .group(
+                            ArgGroup::new("input")
+                                .args(&["json", "json-file"])
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("signers")
+                                .long("signers")
+                                .help("Required signers account ids, in submission order")
+                                .value_name("ACCOUNT")
+                                .multiple_values(true)
+                                .required(true),
+                        )
+                        .arg(proposal_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("approve")
+                        .about("Add the signed approval tx of a signer to the proposal file")
+                        .arg(proposal_arg.clone())
+                        .arg(
+                            Arg::new("private-key")
+                                .long("private-key")
+                                .help("Base58 of the signer private key bytes array in pkcs8 (`-` to read from stdin)")
+                                .value_name("BASE58")
+                                .required_unless_present_any(&["signer-config", "pkcs11-module", "remote-signer"]),
+                        )
+                        .arg(policy_arg())
+                        .arg(audit_log_arg())
+                        .args(signer_args()),
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("Show the approved and missing signers of the proposal")
+                        .arg(proposal_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("submit")
+                        .about("Submit the collected approvals in the signers order")
+                        .arg(proposal_arg)
+                        .arg(url_arg.clone().required(true))
+                        .arg(audit_log_arg()),
+                ),
+        )
+        .subcommand(
+            Command::new("audit")
+                .about("Audit log of the produced signatures")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("verify")
+                        .about("Check the hash chain of the audit log")
+                        .arg(
+                            Arg::new("log")
+                                .long("log")
+                                .help("Audit log file")
+                                .value_name("PATH")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("head")
+                                .long("head")
+                                .help("Head hash of a previous verification, detects a truncated log")
+                                .value_name("HASH")
+                                .requires("count"),
+                        )
+                        .arg(
+                            Arg::new("count")
+                                .long("count")
+                                .help("Entries count of a previous verification")
+                                .value_name("N")
+                                .requires("head"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("signer_server")
+                .about("Serve the signature requests of the remote signer clients")
                 .arg(
-                    Arg::new("string")
-                        .long("string")
-                        .help("String to convert in MessagePack")
-                        .value_name("STRING")
-                        .required_unless_present_any(&["json"]),
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Address to listen on")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1:9000"),
+                )
+                .arg(
+                    Arg::new("config")
+                        .long("config")
+                        .help("Server config file with the signers aliases")
+                        .value_name("PATH")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("mock_node")
+                .about("Serve a mock Trinci node with scripted replies, for offline tests")
+                .arg(
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Address to listen on")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1:8000"),
+                )
+                .arg(
+                    Arg::new("scenario")
+                        .long("scenario")
+                        .help("Json array of the replies to the transactions, accepted when exhausted")
+                        .value_name("PATH"),
+                ),
+        )
+        .subcommand(
+            Command::new("selftest")
+                .about("Check the signing against the golden test vectors")
+                .arg(
+                    Arg::new("vectors")
+                        .long("vectors")
+                        .help("Test vectors file, the built-in set if not given")
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::new("generate")
+                        .long("generate")
+                        .help("Write the expected bytes of the vectors in the file")
+                        .requires("vectors"),
                 ),
         )
+        .subcommand(
+            Command::new("wizard")
+                .about("Build, preview and submit a transaction answering to prompts")
+                .arg(profile_arg.clone()),
+        )
+        .subcommand(
+            Command::new("repl")
+                .about("Interactive session for several queries and transactions")
+                .arg(profile_arg),
+        )
+        .subcommand(payout_command().arg(url_arg.clone().required(true)))
+        .subcommand(
+            Command::new("get_block")
+                .about("Retrieve a block from the Trinci Blockchain by height")
+                .arg(
+                    Arg::new("height")
+                        .long("height")
+                        .help("Block height")
+                        .value_name("HEIGHT")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("txs")
+                        .long("txs")
+                        .help("Include the hashes of the block transactions"),
+                )
+                .arg(url_arg.clone().required(true)),
+        )
+        .subcommand(
+            Command::new("get_tx")
+                .about("Retrieve a transaction from the Trinci Blockchain by hash")
+                .arg(
+                    Arg::new("hash")
+                        .long("hash")
+                        .help("Transaction hash in HEX (multihash)")
+                        .value_name("HASH")
+                        .required(true),
+                )
+                .arg(url_arg.clone().required(true)),
+        )
+        .subcommand(
+            Command::new("get_receipt")
+                .about("Retrieve a transaction receipt from the Trinci Blockchain by hash")
+                .arg(
+                    Arg::new("hash")
+                        .long("hash")
+                        .help("Transaction hash in HEX (multihash)")
+                        .value_name("HASH")
+                        .required(true),
+                )
+                .arg(url_arg.clone().required(true))
+                .arg(abi_arg.help("Contracts interface descriptors file, to decode the returns"))
+                .arg(fuel_history_arg().help("History file where the fuel burned by the transaction is recorded")),
+        )
+        .subcommand(
+            Command::new("node_info")
+                .about("Retrieve network, block height, pending transactions and version of a Trinci Node")
+                .arg(url_arg.required(true)),
+        )
 }
 
-fn get_inner_args(matches: &ArgMatches) -> Option<UnitTxArgs> {
-    if let Some(hex_text) = matches.value_of("hex") {
-        UnitTxArgs::from_hex_string(hex_text)
-    } else if let Some(json_text) = matches.value_of("json") {
-        UnitTxArgs::from_json_string(json_text)
-    } else if let Some(bs58_text) = matches.value_of("bs58") {
-        UnitTxArgs::from_bs58_string(bs58_text)
+/// Read the whole content of a file, `-` stands for the standard input
+pub fn read_input(path: &str) -> Result<Vec<u8>> {
+    if path == "-" {
+        let mut buf = vec![];
+        io::stdin().read_to_end(&mut buf)?;
+        Ok(buf)
     } else {
-        eprintln!("Args error");
-        None
+        std::fs::read(path).map_err(|e| e.into())
     }
 }
 
-pub fn get_args() -> Option<AppCommand> {
-    let matches = create_app().get_matches();
-
-    match matches.subcommand() {
-        Some(("create_unit_tx", sub_matches)) => match get_inner_args(sub_matches) {
-            Some(args) => Some(AppCommand {
-                operation: AppOperation::CreateUnitTx,
-                args: Arguments::UnitTxArgsType(args),
-                url: String::new(),
-            }),
-            None => {
-                eprintln!("Invalid command");
+/// Get the value of a text argument, `-` stands for the standard input
+fn get_text_arg(matches: &ArgMatches, name: &str) -> Option<String> {
+    match matches.value_of(name)? {
+        "-" => match read_input("-").map(String::from_utf8) {
+            Ok(Ok(text)) => Some(text.trim().to_string()),
+            _ => {
+                eprintln!("Error reading {} from stdin", name);
                 None
             }
         },
-        Some(("submit_unit_tx", sub_matches)) => {
-            let url = match sub_matches.value_of("url") {
-                Some(val) => val.to_string(),
-                None => return None,
+        text => Some(text.to_string()),
+    }
+}
+
+/// Get the private key argument, `-` stands for the standard input.
+/// The buffers holding the key are wiped once it is extracted.
+/// The key is empty if not given, as with a signer backend.
+fn get_private_key_arg(matches: &ArgMatches) -> Option<PrivateKey> {
+    match matches.value_of("private-key").unwrap_or_default() {
+        "" => Some(PrivateKey::default()),
+        "-" => {
+            let buf = match read_input("-") {
+                Ok(buf) => Zeroizing::new(buf),
+                Err(_) => {
+                    eprintln!("Error reading private-key from stdin");
+                    return None;
+                }
             };
-            match get_inner_args(sub_matches) {
-                Some(args) => Some(AppCommand {
-                    operation: AppOperation::SubmitUnitTx,
-                    args: Arguments::UnitTxArgsType(args),
-                    url,
-                }),
-                None => {
-                    eprintln!("Invalid command");
+            match std::str::from_utf8(&buf) {
+                Ok(text) => Some(PrivateKey::new(text.trim().to_string())),
+                Err(_) => {
+                    eprintln!("Error reading private-key from stdin");
                     None
                 }
             }
         }
-        Some(("to_message_pack", sub_matches)) => {
-            let msg_pack_args = if let Some(json_text) = sub_matches.value_of("json") {
-                match Value::from_str(json_text) {
-                    Ok(val) => Arguments::MsgPackStruct(val),
-                    Err(_) => return None,
-                }
-            } else if let Some(string_text) = sub_matches.value_of("string") {
-                Arguments::MsgPackString(string_text.to_string())
-            } else {
+        text => Some(PrivateKey::new(text.to_string())),
+    }
+}
+
+/// Get the content of a file argument
+fn get_file_arg(matches: &ArgMatches, name: &str) -> Option<Vec<u8>> {
+    let path = matches.value_of(name)?;
+    match read_input(path) {
+        Ok(buf) => Some(buf),
+        Err(e) => {
+            eprintln!("Error reading {} `{}`: {}", name, path, e);
+            None
+        }
+    }
+}
+
+fn get_template_arg(matches: &ArgMatches) -> Option<Template> {
+    let path = matches.value_of("template")?;
+    match Template::load(path) {
+        Ok(template) => Some(template),
+        Err(e) => {
+            eprintln!("Error loading template `{}`: {}", path, e);
+            None
+        }
+    }
+}
+
+fn get_set_args(matches: &ArgMatches) -> Option<Vars> {
+    let mut vars = Vars::new();
+    for text in matches.values_of("set").into_iter().flatten() {
+        match parse_set(text) {
+            Ok((name, value)) => {
+                vars.insert(name, value);
+            }
+            Err(e) => {
+                eprintln!("Invalid --set: {}", e);
                 return None;
-            };
-            Some(AppCommand {
-                operation: AppOperation::ToMessagePack,
-                args: msg_pack_args,
-                url: String::new(),
-            })
+            }
         }
+    }
+    Some(vars)
+}
 
-        _ => None,
+/// Fill the `--template` with the `--set` values
+fn get_template_args(matches: &ArgMatches) -> Option<UnitTxArgs> {
+    let template = get_template_arg(matches)?;
+    match template.fill(&get_set_args(matches)?, get_private_key_arg(matches)?) {
+        Ok(args) => Some(args),
+        Err(e) => {
+            eprintln!(
+                "Error filling template `{}`: {}",
+                matches.value_of("template")?,
+                e
+            );
+            None
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Fill the `--template` with every `--rows` row, the `--set` values are
+/// shared by the rows and a row column overrides them
+fn get_template_rows_args(matches: &ArgMatches) -> Option<Vec<UnitTxArgs>> {
+    let template = get_template_arg(matches)?;
+    let set_vars = get_set_args(matches)?;
+    let private_key = get_private_key_arg(matches)?;
+    let rows = match rows_from_csv(&get_file_arg(matches, "rows")?) {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Error reading rows `{}`: {}", matches.value_of("rows")?, e);
+            return None;
+        }
+    };
 
-    #[test]
-    fn test_create_unit_tx_json_command() {
-        let command =
-            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--json", "any"]);
-        assert!(command.is_ok())
+    let mut txs_args = Vec::with_capacity(rows.len());
+    let mut errors = vec![];
+    for (i, row) in rows.into_iter().enumerate() {
+        let mut vars = set_vars.clone();
+        vars.extend(row);
+        match template.fill(&vars, private_key.clone()) {
+            Ok(args) => txs_args.push(args),
+            Err(e) => errors.push(format!("row {}: {}", i + 1, e)),
+        }
     }
-    #[test]
-    fn test_create_unit_tx_bs58_command() {
-        let command =
-            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--bs58", "any"]);
-        assert!(command.is_ok())
+    if !errors.is_empty() {
+        eprintln!("Error filling template rows: {}", errors.join("; "));
+        return None;
     }
-    #[test]
-    fn test_create_unit_tx_hex_command() {
-        let command =
-            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--hex", "any"]);
-        assert!(command.is_ok())
+    if txs_args.is_empty() {
+        eprintln!("No rows in `{}`", matches.value_of("rows")?);
+        return None;
     }
+    Some(txs_args)
+}
 
-    #[test]
-    fn test_submit_unit_tx_json_command() {
-        let command = create_app().try_get_matches_from(vec![
-            "prog",
-            "submit_unit_tx",
-            "--json",
-            "any",
-            "--url",
-            "any_url",
-        ]);
-        assert!(command.is_ok())
+/// Unit tx args of `create_unit_tx` and `submit_unit_tx`, a batch with `--rows`
+fn get_unit_tx_input(matches: &ArgMatches) -> Option<Arguments> {
+    if matches.is_present("rows") {
+        Some(Arguments::UnitTxBatchArgs(get_template_rows_args(matches)?))
+    } else {
+        Some(Arguments::UnitTxArgsType(get_inner_args(matches)?))
     }
-    #[test]
-    fn test_submit_unit_tx_bs58_command() {
-        let command = create_app().try_get_matches_from(vec![
-            "prog",
-            "submit_unit_tx",
-            "--bs58",
-            "any",
-            "--url",
-            "any_url",
-        ]);
-        assert!(command.is_ok())
+}
+
+fn get_inner_args(matches: &ArgMatches) -> Option<UnitTxArgs> {
+    // The inputs hold the private key, they are wiped once parsed
+    let args = if matches.is_present("hex") {
+        UnitTxArgs::from_hex_string(&Zeroizing::new(get_text_arg(matches, "hex")?))
+    } else if matches.is_present("json") {
+        UnitTxArgs::from_json_string(&Zeroizing::new(get_text_arg(matches, "json")?))
+    } else if matches.is_present("bs58") {
+        UnitTxArgs::from_bs58_string(&Zeroizing::new(get_text_arg(matches, "bs58")?))
+    } else if matches.is_present("json-file") {
+        let buf = Zeroizing::new(get_file_arg(matches, "json-file")?);
+        match std::str::from_utf8(&buf) {
+            Ok(json_text) => UnitTxArgs::from_json_string(json_text),
+            Err(_) => None,
+        }
+    } else if matches.is_present("msgpack-file") {
+        UnitTxArgs::from_msgpack(&Zeroizing::new(get_file_arg(matches, "msgpack-file")?))
+    } else if matches.is_present("template") {
+        Some(get_template_args(matches)?)
+    } else {
+        eprintln!("Args error");
+        None
+    };
+
+    match (args, matches.is_present("args-file")) {
+        (Some(mut args), true) => {
+            args.args = serde_value::Value::Bytes(get_file_arg(matches, "args-file")?);
+            Some(args)
+        }
+        (args, _) => args,
     }
-    #[test]
-    fn test_submit_unit_tx_hex_command() {
-        let command = create_app().try_get_matches_from(vec![
-            "prog",
-            "submit_unit_tx",
-            "--hex",
-            "any",
-            "--url",
-            "any_url",
-        ]);
-        assert!(command.is_ok())
+}
+
+/// Get the json unit tx args of `encode_args`
+fn get_json_args(matches: &ArgMatches) -> Option<UnitTxArgs> {
+    let args = if matches.is_present("json") {
+        UnitTxArgs::from_json_string(&Zeroizing::new(get_text_arg(matches, "json")?))
+    } else {
+        let buf = Zeroizing::new(get_file_arg(matches, "json-file")?);
+        std::str::from_utf8(&buf)
+            .ok()
+            .and_then(UnitTxArgs::from_json_string)
+    };
+    if args.is_none() {
+        eprintln!("Invalid json unit tx args");
     }
+    args
+}
 
-    #[test]
-    fn test_to_message_pack_json_command() {
-        let command =
-            create_app().try_get_matches_from(vec!["prog", "to_message_pack", "--json", "any"]);
-        assert!(command.is_ok())
+/// Get the messagepacked unit tx args of `decode_args`
+fn get_msgpack_args(matches: &ArgMatches) -> Option<UnitTxArgs> {
+    let args = if matches.is_present("hex") {
+        UnitTxArgs::from_hex_string(&Zeroizing::new(get_text_arg(matches, "hex")?))
+    } else if matches.is_present("bs58") {
+        UnitTxArgs::from_bs58_string(&Zeroizing::new(get_text_arg(matches, "bs58")?))
+    } else {
+        UnitTxArgs::from_msgpack(&Zeroizing::new(get_file_arg(matches, "msgpack-file")?))
+    };
+    if args.is_none() {
+        eprintln!("Invalid messagepack unit tx args");
     }
-    #[test]
+    args
+}
+
+fn get_fuel_arg(matches: &ArgMatches, name: &str) -> Option<Option<u64>> {
+    match matches.value_of(name).map(u64::from_str) {
+        Some(Ok(fuel)) => Some(Some(fuel)),
+        Some(Err(_)) => {
+            eprintln!("Invalid {} value", name);
+            None
+        }
+        None => Some(None),
+    }
+}
+
+/// Options of the subcommands that produce bytes
+fn get_output_options(matches: &ArgMatches) -> AppOptions {
+    AppOptions {
+        encoding: matches
+            .value_of("encoding")
+            .and_then(|encoding| Encoding::from_str(encoding).ok()),
+        out: matches.value_of("out").map(str::to_string),
+        ..Default::default()
+    }
+}
+
+fn get_abi_arg(matches: &ArgMatches) -> Option<Option<AbiRegistry>> {
+    match matches
+        .value_of("abi")
+        .map(|path| (path, AbiRegistry::load(path)))
+    {
+        Some((_, Ok(abi))) => Some(Some(abi)),
+        Some((path, Err(e))) => {
+            eprintln!("Error loading abi `{}`: {}", path, e);
+            None
+        }
+        None => Some(None),
+    }
+}
+
+fn get_policy_arg(matches: &ArgMatches) -> Option<Option<SigningPolicy>> {
+    match matches
+        .value_of("policy")
+        .map(|path| (path, SigningPolicy::load(path)))
+    {
+        Some((_, Ok(policy))) => Some(Some(policy)),
+        Some((path, Err(e))) => {
+            eprintln!("Error loading policy `{}`: {}", path, e);
+            None
+        }
+        None => Some(None),
+    }
+}
+
+fn get_signer_arg(matches: &ArgMatches) -> Option<Option<SignerConfig>> {
+    if let Some(path) = matches.value_of("signer-config") {
+        match SignerConfig::load(path) {
+            Ok(config) => Some(Some(config)),
+            Err(e) => {
+                eprintln!("Error loading signer config `{}`: {}", path, e);
+                None
+            }
+        }
+    } else if let Some(module) = matches.value_of("pkcs11-module") {
+        Some(Some(SignerConfig::Pkcs11 {
+            module: module.to_string(),
+            token: matches.value_of("pkcs11-token").map(str::to_string),
+            key: matches.value_of("pkcs11-key")?.to_string(),
+            pin_env: PKCS11_PIN_ENV.to_string(),
+        }))
+    } else if let Some(url) = matches.value_of("remote-signer") {
+        Some(Some(SignerConfig::Remote {
+            url: url.to_string(),
+            alias: matches.value_of("remote-alias")?.to_string(),
+            token_env: REMOTE_SIGNER_TOKEN_ENV.to_string(),
+        }))
+    } else {
+        Some(None)
+    }
+}
+
+fn get_fuel_bounds_arg(matches: &ArgMatches) -> Option<FuelBounds> {
+    let default_bounds = FuelBounds::default();
+    Some(FuelBounds {
+        min: get_fuel_arg(matches, "min-fuel")?.unwrap_or(default_bounds.min),
+        max: get_fuel_arg(matches, "max-fuel")?.unwrap_or(default_bounds.max),
+    })
+}
+
+/// Fuel limit requested with `--fuel`, a number or `auto`
+fn get_fuel_setting_arg(matches: &ArgMatches) -> Option<Option<FuelSetting>> {
+    match matches.value_of("fuel") {
+        Some("auto") => Some(Some(FuelSetting::Auto {
+            margin: get_fuel_arg(matches, "fuel-margin")?.unwrap_or(DEFAULT_FUEL_MARGIN),
+            cap: get_fuel_arg(matches, "fuel-cap")?,
+        })),
+        Some(_) => Some(get_fuel_arg(matches, "fuel")?.map(FuelSetting::Fixed)),
+        None => Some(None),
+    }
+}
+
+fn get_profile_arg(matches: &ArgMatches) -> Option<Option<Profile>> {
+    match matches
+        .value_of("profile")
+        .map(|path| (path, Profile::load(path)))
+    {
+        Some((_, Ok(profile))) => Some(Some(profile)),
+        Some((path, Err(e))) => {
+            eprintln!("Error loading profile `{}`: {}", path, e);
+            None
+        }
+        None => Some(None),
+    }
+}
+
+/// Options shared by the subcommands that build a unit tx
+fn get_unit_tx_options(matches: &ArgMatches) -> Option<AppOptions> {
+    Some(AppOptions {
+        lenient: matches.is_present("lenient"),
+        fuel_bounds: get_fuel_bounds_arg(matches)?,
+        fuel: get_fuel_setting_arg(matches)?,
+        fuel_history: matches.value_of("fuel-history").map(str::to_string),
+        abi: get_abi_arg(matches)?,
+        policy: get_policy_arg(matches)?,
+        audit_log: matches.value_of("audit-log").map(str::to_string),
+        signer: get_signer_arg(matches)?,
+        ..Default::default()
+    })
+}
+
+fn get_asset_command(matches: &ArgMatches) -> Option<AppCommand> {
+    let (method, sub_matches) = matches.subcommand()?;
+
+    let units = match sub_matches.value_of("units").map(u64::from_str) {
+        Some(Ok(units)) => units,
+        Some(Err(_)) => {
+            eprintln!("Invalid units value");
+            return None;
+        }
+        None => 0,
+    };
+    let account = |name: &str| sub_matches.value_of(name).unwrap_or_default().to_string();
+    let operation = match method {
+        "transfer" => AssetOperation::Transfer {
+            from: account("from"),
+            to: account("to"),
+            units,
+        },
+        "mint" => AssetOperation::Mint {
+            to: account("to"),
+            units,
+        },
+        "burn" => AssetOperation::Burn {
+            from: account("from"),
+            units,
+        },
+        "balance" => AssetOperation::Balance,
+        _ => return None,
+    };
+
+    let fuel = match sub_matches.value_of("fuel").map(u64::from_str) {
+        Some(Ok(fuel)) => fuel,
+        _ => {
+            eprintln!("Invalid fuel value");
+            return None;
+        }
+    };
+
+    let args = UnitTxArgs {
+        target: sub_matches.value_of("asset")?.to_string(),
+        network: sub_matches.value_of("network")?.to_string(),
+        fuel,
+        contract: sub_matches
+            .value_of("contract")
+            .unwrap_or_default()
+            .to_string(),
+        method: operation.method().to_string(),
+        args: operation.args(),
+        private_key: get_private_key_arg(sub_matches)?,
+    };
+
+    let (operation, url) = match sub_matches.value_of("url") {
+        Some(url) => (AppOperation::SubmitUnitTx, url.to_string()),
+        None => (AppOperation::CreateUnitTx, String::new()),
+    };
+    Some(AppCommand {
+        operation,
+        args: Arguments::UnitTxArgsType(args),
+        url,
+        options: AppOptions {
+            policy: get_policy_arg(sub_matches)?,
+            audit_log: sub_matches.value_of("audit-log").map(str::to_string),
+            signer: get_signer_arg(sub_matches)?,
+            ..get_output_options(sub_matches)
+        },
+    })
+}
+
+fn get_payout_command(matches: &ArgMatches) -> Option<AppCommand> {
+    let tx = match matches.value_of("template") {
+        Some(path) => match Template::load(path) {
+            Ok(template) => PayoutTx::Template(template),
+            Err(e) => {
+                eprintln!("Error loading template `{}`: {}", path, e);
+                return None;
+            }
+        },
+        None => PayoutTx::Transfer {
+            asset: matches.value_of("asset")?.to_string(),
+            network: matches.value_of("network")?.to_string(),
+            fuel: get_fuel_arg(matches, "fuel")??,
+            contract: matches.value_of("contract").unwrap_or_default().to_string(),
+            from: matches.value_of("from")?.to_string(),
+        },
+    };
+
+    let bulk_size = match matches.value_of("bulk-size").map(usize::from_str) {
+        Some(Ok(bulk_size)) if bulk_size > 0 => bulk_size,
+        _ => {
+            eprintln!("Invalid bulk-size value");
+            return None;
+        }
+    };
+    let interval = match matches.value_of("interval").map(u64::from_str) {
+        Some(Ok(interval)) => Duration::from_millis(interval),
+        _ => {
+            eprintln!("Invalid interval value");
+            return None;
+        }
+    };
+
+    let config = PayoutConfig {
+        rows: matches.value_of("csv")?.to_string(),
+        result: matches.value_of("result")?.to_string(),
+        tx,
+        private_key: get_private_key_arg(matches)?,
+        bulk_size,
+        interval,
+    };
+    Some(AppCommand {
+        operation: AppOperation::Payout,
+        args: Arguments::PayoutArgs(config),
+        url: matches.value_of("url")?.to_string(),
+        options: AppOptions {
+            policy: get_policy_arg(matches)?,
+            audit_log: matches.value_of("audit-log").map(str::to_string),
+            signer: get_signer_arg(matches)?,
+            ..Default::default()
+        },
+    })
+}
+
+fn get_multisig_command(matches: &ArgMatches) -> Option<AppCommand> {
+    let (action, sub_matches) = matches.subcommand()?;
+    let proposal = sub_matches.value_of("proposal")?.to_string();
+
+    let command = match action {
+        "propose" => {
+            let buf = if sub_matches.is_present("json") {
+                get_text_arg(sub_matches, "json")?.into_bytes()
+            } else {
+                get_file_arg(sub_matches, "json-file")?
+            };
+            let tx = match TxTemplate::from_json_slice(&buf) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    eprintln!("Invalid approval tx args: {}", e);
+                    return None;
+                }
+            };
+            MultisigCommand::Propose {
+                tx,
+                signers: sub_matches
+                    .values_of("signers")?
+                    .map(str::to_string)
+                    .collect(),
+                proposal,
+            }
+        }
+        "approve" => MultisigCommand::Approve {
+            proposal,
+            private_key: get_private_key_arg(sub_matches)?,
+        },
+        "status" => MultisigCommand::Status { proposal },
+        "submit" => MultisigCommand::Submit { proposal },
+        _ => return None,
+    };
+
+    let url = match action {
+        "submit" => sub_matches.value_of("url")?.to_string(),
+        _ => String::new(),
+    };
+    let options = match action {
+        "approve" => AppOptions {
+            policy: get_policy_arg(sub_matches)?,
+            audit_log: sub_matches.value_of("audit-log").map(str::to_string),
+            signer: get_signer_arg(sub_matches)?,
+            ..Default::default()
+        },
+        "submit" => AppOptions {
+            audit_log: sub_matches.value_of("audit-log").map(str::to_string),
+            ..Default::default()
+        },
+        _ => AppOptions::default(),
+    };
+    Some(AppCommand {
+        operation: AppOperation::Multisig,
+        args: Arguments::MultisigArgs(command),
+        url,
+        options,
+    })
+}
+
+pub fn get_args() -> Option<AppCommand> {
+    let matches = create_app().get_matches();
+
+    match matches.subcommand() {
+        Some(("create_unit_tx", sub_matches)) => match get_unit_tx_input(sub_matches) {
+            Some(args) => {
+                let output_options = get_output_options(sub_matches);
+                Some(AppCommand {
+                    operation: AppOperation::CreateUnitTx,
+                    args,
+                    url: String::new(),
+                    options: AppOptions {
+                        encoding: output_options.encoding,
+                        out: output_options.out,
+                        ..get_unit_tx_options(sub_matches)?
+                    },
+                })
+            }
+            None => {
+                eprintln!("Invalid command");
+                None
+            }
+        },
+        Some(("submit_unit_tx", sub_matches)) => {
+            let url = sub_matches.value_of("url").unwrap_or_default().to_string();
+            match get_unit_tx_input(sub_matches) {
+                Some(args) => Some(AppCommand {
+                    operation: AppOperation::SubmitUnitTx,
+                    args,
+                    url,
+                    options: AppOptions {
+                        check_network: sub_matches.is_present("check-network"),
+                        dry_run: sub_matches.is_present("dry-run"),
+                        ..get_unit_tx_options(sub_matches)?
+                    },
+                }),
+                None => {
+                    eprintln!("Invalid command");
+                    None
+                }
+            }
+        }
+        Some(("encode_args", sub_matches)) => Some(AppCommand {
+            operation: AppOperation::EncodeArgs,
+            args: Arguments::UnitTxArgsType(get_json_args(sub_matches)?),
+            url: String::new(),
+            options: AppOptions {
+                lenient: sub_matches.is_present("lenient"),
+                fuel_bounds: get_fuel_bounds_arg(sub_matches)?,
+                ..get_output_options(sub_matches)
+            },
+        }),
+        Some(("decode_args", sub_matches)) => Some(AppCommand {
+            operation: AppOperation::DecodeArgs,
+            args: Arguments::UnitTxArgsType(get_msgpack_args(sub_matches)?),
+            url: String::new(),
+            options: AppOptions::default(),
+        }),
+        Some((name @ ("wizard" | "repl"), sub_matches)) => Some(AppCommand {
+            operation: AppOperation::from_str(name).ok()?,
+            args: Arguments::InteractiveArgs {
+                profile: get_profile_arg(sub_matches)?,
+            },
+            url: String::new(),
+            options: AppOptions::default(),
+        }),
+        Some(("to_message_pack", sub_matches)) => {
+            let msg_pack_args = if sub_matches.is_present("json") {
+                match Value::from_str(&get_text_arg(sub_matches, "json")?) {
+                    Ok(val) => Arguments::MsgPackStruct(val),
+                    Err(_) => return None,
+                }
+            } else if sub_matches.is_present("json-file") {
+                match serde_json::from_slice(&get_file_arg(sub_matches, "json-file")?) {
+                    Ok(val) => Arguments::MsgPackStruct(val),
+                    Err(_) => return None,
+                }
+            } else if sub_matches.is_present("string") {
+                Arguments::MsgPackString(get_text_arg(sub_matches, "string")?)
+            } else {
+                return None;
+            };
+            Some(AppCommand {
+                operation: AppOperation::ToMessagePack,
+                args: msg_pack_args,
+                url: String::new(),
+                options: get_output_options(sub_matches),
+            })
+        }
+        Some(("from_message_pack", sub_matches)) => {
+            let buf = if sub_matches.is_present("file") {
+                get_file_arg(sub_matches, "file")?
+            } else {
+                let (name, res): (&str, Result<Vec<u8>>) = if sub_matches.is_present("hex") {
+                    (
+                        "hex",
+                        hex::decode(get_text_arg(sub_matches, "hex")?).map_err(|e| e.into()),
+                    )
+                } else if sub_matches.is_present("bs58") {
+                    ("bs58", bs58_into_vec(&get_text_arg(sub_matches, "bs58")?))
+                } else if sub_matches.is_present("base64") {
+                    let text = get_text_arg(sub_matches, "base64")?;
+                    ("base64", base64::decode(text).map_err(|e| e.into()))
+                } else {
+                    let text = get_text_arg(sub_matches, "bytes")?;
+                    (
+                        "bytes",
+                        serde_json::from_str::<Vec<u8>>(&text).map_err(|e| e.into()),
+                    )
+                };
+                match res {
+                    Ok(buf) => buf,
+                    Err(e) => {
+                        eprintln!("Invalid {} input: {}", name, e);
+                        return None;
+                    }
+                }
+            };
+            Some(AppCommand {
+                operation: AppOperation::FromMessagePack,
+                args: Arguments::MsgPackBytes(buf),
+                url: String::new(),
+                options: AppOptions::default(),
+            })
+        }
+        Some(("asset", sub_matches)) => get_asset_command(sub_matches),
+        Some(("multisig", sub_matches)) => get_multisig_command(sub_matches),
+        Some(("payout", sub_matches)) => get_payout_command(sub_matches),
+        Some(("signer_server", sub_matches)) => {
+            let path = sub_matches.value_of("config")?;
+            let config = match ServerConfig::load(path) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Error loading signer server config `{}`: {}", path, e);
+                    return None;
+                }
+            };
+            Some(AppCommand {
+                operation: AppOperation::SignerServer,
+                args: Arguments::SignerServerArgs {
+                    listen: sub_matches.value_of("listen")?.to_string(),
+                    config,
+                },
+                url: String::new(),
+                options: AppOptions::default(),
+            })
+        }
+        Some(("selftest", sub_matches)) => Some(AppCommand {
+            operation: AppOperation::Selftest,
+            args: Arguments::SelftestArgs {
+                vectors: sub_matches.value_of("vectors").map(str::to_string),
+                generate: sub_matches.is_present("generate"),
+            },
+            url: String::new(),
+            options: AppOptions::default(),
+        }),
+        Some(("mock_node", sub_matches)) => {
+            let scenario = match sub_matches.value_of("scenario") {
+                Some(path) => match load_scenario(path) {
+                    Ok(scenario) => scenario,
+                    Err(e) => {
+                        eprintln!("Error loading mock node scenario `{}`: {}", path, e);
+                        return None;
+                    }
+                },
+                None => vec![],
+            };
+            Some(AppCommand {
+                operation: AppOperation::MockNode,
+                args: Arguments::MockNodeArgs {
+                    listen: sub_matches.value_of("listen")?.to_string(),
+                    scenario,
+                },
+                url: String::new(),
+                options: AppOptions::default(),
+            })
+        }
+        Some(("audit", sub_matches)) => match sub_matches.subcommand() {
+            Some(("verify", verify_matches)) => {
+                let checkpoint = match verify_matches.value_of("count").map(u64::from_str) {
+                    Some(Ok(count)) => Some(Checkpoint {
+                        count,
+                        head: verify_matches.value_of("head")?.to_string(),
+                    }),
+                    Some(Err(_)) => {
+                        eprintln!("Invalid entries count");
+                        return None;
+                    }
+                    None => None,
+                };
+                Some(AppCommand {
+                    operation: AppOperation::Audit,
+                    args: Arguments::AuditVerifyArgs {
+                        log: verify_matches.value_of("log")?.to_string(),
+                        checkpoint,
+                    },
+                    url: String::new(),
+                    options: AppOptions::default(),
+                })
+            }
+            _ => None,
+        },
+        Some(("get_block", sub_matches)) => {
+            let url = sub_matches.value_of("url")?.to_string();
+            let height = match sub_matches.value_of("height").map(u64::from_str) {
+                Some(Ok(height)) => height,
+                _ => {
+                    eprintln!("Invalid block height");
+                    return None;
+                }
+            };
+            Some(AppCommand {
+                operation: AppOperation::GetBlock,
+                args: Arguments::GetBlockArgs {
+                    height,
+                    txs: sub_matches.is_present("txs"),
+                },
+                url,
+                options: AppOptions::default(),
+            })
+        }
+        Some(("get_tx", sub_matches)) => {
+            let url = sub_matches.value_of("url")?.to_string();
+            let hash = sub_matches.value_of("hash")?.to_string();
+            Some(AppCommand {
+                operation: AppOperation::GetTx,
+                args: Arguments::GetTxArgs { hash },
+                url,
+                options: AppOptions::default(),
+            })
+        }
+        Some(("get_receipt", sub_matches)) => {
+            let url = sub_matches.value_of("url")?.to_string();
+            let hash = sub_matches.value_of("hash")?.to_string();
+            Some(AppCommand {
+                operation: AppOperation::GetReceipt,
+                args: Arguments::GetReceiptArgs { hash },
+                url,
+                options: AppOptions {
+                    abi: get_abi_arg(sub_matches)?,
+                    fuel_history: sub_matches.value_of("fuel-history").map(str::to_string),
+                    ..Default::default()
+                },
+            })
+        }
+        Some(("node_info", sub_matches)) => {
+            let url = sub_matches.value_of("url")?.to_string();
+            Some(AppCommand {
+                operation: AppOperation::NodeInfo,
+                args: Arguments::None,
+                url,
+                options: AppOptions::default(),
+            })
+        }
+
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_unit_tx_json_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--json", "any"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_bs58_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--bs58", "any"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_hex_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--hex", "any"]);
+        assert!(command.is_ok())
+    }
+
+    #[test]
+    fn test_submit_unit_tx_json_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_bs58_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--bs58",
+            "any",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_hex_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--hex",
+            "any",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+
+    #[test]
+    fn test_to_message_pack_json_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "to_message_pack", "--json", "any"]);
+        assert!(command.is_ok())
+    }
+    #[test]
     fn test_to_message_pack_string_command() {
         let command =
             create_app().try_get_matches_from(vec!["prog", "to_message_pack", "--string", "any"]);
         assert!(command.is_ok())
     }
+
+    #[test]
+    fn test_create_unit_tx_json_file_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json-file",
+            "args.json",
+            "--args-file",
+            "document.pdf",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_msgpack_file_stdin_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--msgpack-file",
+            "-",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_multiple_inputs_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--msgpack-file",
+            "args.bin",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_submit_unit_tx_json_file_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json-file",
+            "args.json",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_to_message_pack_json_file_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "to_message_pack", "--json-file", "-"]);
+        assert!(command.is_ok())
+    }
+
+    #[test]
+    fn test_from_message_pack_hex_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "from_message_pack", "--hex", "any"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_from_message_pack_bytes_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "from_message_pack",
+            "--bytes",
+            "[145,1]",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_from_message_pack_multiple_inputs_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "from_message_pack",
+            "--base64",
+            "any",
+            "--file",
+            "-",
+        ]);
+        assert!(command.is_err())
+    }
+
+    #[test]
+    fn test_create_unit_tx_encoding_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--encoding",
+            "base64",
+            "--out",
+            "tx.txt",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_to_message_pack_invalid_encoding_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "to_message_pack",
+            "--string",
+            "any",
+            "--encoding",
+            "base32",
+        ]);
+        assert!(command.is_err())
+    }
+
+    #[test]
+    fn test_asset_transfer_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "asset",
+            "transfer",
+            "--asset",
+            "#ASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--private-key",
+            "any",
+            "--from",
+            "#FROM",
+            "--to",
+            "#TO",
+            "--units",
+            "100",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_asset_transfer_with_pkcs11_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "asset",
+            "transfer",
+            "--asset",
+            "#ASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--pkcs11-module",
+            "/usr/lib/softhsm/libsofthsm2.so",
+            "--pkcs11-key",
+            "treasury",
+            "--from",
+            "#FROM",
+            "--to",
+            "#TO",
+            "--units",
+            "100",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_asset_transfer_pkcs11_without_key_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "asset",
+            "transfer",
+            "--asset",
+            "#ASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--pkcs11-module",
+            "/usr/lib/softhsm/libsofthsm2.so",
+            "--from",
+            "#FROM",
+            "--to",
+            "#TO",
+            "--units",
+            "100",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_submit_unit_tx_with_remote_signer_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json-file",
+            "tx.json",
+            "--url",
+            "http://localhost:8000/api/v1",
+            "--remote-signer",
+            "http://signer.local:9000",
+            "--remote-alias",
+            "treasury",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_signer_server_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "signer_server",
+            "--listen",
+            "0.0.0.0:9000",
+            "--config",
+            "signer.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_encode_args_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "encode_args",
+            "--json-file",
+            "tx.json",
+            "--encoding",
+            "bs58",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_encode_args_raw_encoding_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "encode_args",
+            "--json",
+            "{}",
+            "--encoding",
+            "raw",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_decode_args_command() {
+        let command = create_app().try_get_matches_from(vec!["prog", "decode_args", "--bs58", "-"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_wizard_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "wizard", "--profile", "support.json"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_repl_command() {
+        let command = create_app().try_get_matches_from(vec!["prog", "repl"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_selftest_generate_without_vectors_command() {
+        let command = create_app().try_get_matches_from(vec!["prog", "selftest", "--generate"]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_mock_node_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "mock_node",
+            "--listen",
+            "127.0.0.1:8001",
+            "--scenario",
+            "scenario.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_asset_mint_without_units_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "asset",
+            "mint",
+            "--asset",
+            "#ASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--private-key",
+            "any",
+            "--to",
+            "#TO",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_asset_balance_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "asset",
+            "balance",
+            "--asset",
+            "#ASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--private-key",
+            "any",
+        ]);
+        assert!(command.is_ok())
+    }
+
+    #[test]
+    fn test_multisig_propose_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "multisig",
+            "propose",
+            "--json-file",
+            "approval.json",
+            "--signers",
+            "SIGNER1",
+            "SIGNER2",
+            "--proposal",
+            "proposal.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_multisig_approve_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "multisig",
+            "approve",
+            "--proposal",
+            "proposal.json",
+            "--private-key",
+            "-",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_with_policy_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json-file",
+            "tx.json",
+            "--policy",
+            "policy.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_with_audit_log_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json-file",
+            "tx.json",
+            "--url",
+            "http://localhost:8000/api/v1",
+            "--audit-log",
+            "audit.log",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_audit_verify_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "audit",
+            "verify",
+            "--log",
+            "audit.log",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_audit_verify_with_checkpoint_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "audit",
+            "verify",
+            "--log",
+            "audit.log",
+            "--head",
+            "1220ab",
+            "--count",
+            "42",
+        ]);
+        assert!(command.is_ok());
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "audit",
+            "verify",
+            "--log",
+            "audit.log",
+            "--head",
+            "1220ab",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_multisig_submit_without_url_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "multisig",
+            "submit",
+            "--proposal",
+            "proposal.json",
+        ]);
+        assert!(command.is_err())
+    }
+
+    #[test]
+    fn test_get_block_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "get_block",
+            "--height",
+            "10",
+            "--txs",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_get_block_without_height_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "get_block", "--url", "any_url"]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_submit_unit_tx_check_network_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+            "--check-network",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_dry_run_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+            "--dry-run",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_dry_run_without_url_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--dry-run",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_lenient_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--lenient",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_lenient_with_fuel_bounds_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--lenient",
+            "--max-fuel",
+            "1000",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_submit_unit_tx_fuel_bounds_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+            "--min-fuel",
+            "10",
+            "--max-fuel",
+            "1000",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_submit_unit_tx_fuel_auto_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+            "--fuel",
+            "auto",
+            "--fuel-history",
+            "fuel.jsonl",
+            "--fuel-margin",
+            "30",
+            "--fuel-cap",
+            "5000",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_fuel_auto_requires_history() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--fuel",
+            "auto",
+        ]);
+        assert!(command.is_err());
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--fuel",
+            "1000",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_template_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--template",
+            "transfer.json",
+            "--set",
+            "to=#BOB",
+            "--set",
+            "units=10",
+            "--private-key",
+            "-",
+        ]);
+        assert!(command.is_ok());
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--json",
+            "any",
+            "--url",
+            "any_url",
+            "--set",
+            "to=#BOB",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_unit_tx_template_rows_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--template",
+            "transfer.json",
+            "--rows",
+            "rows.csv",
+            "--set",
+            "fuel=1000",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok());
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "create_unit_tx", "--rows", "rows.csv"]);
+        assert!(command.is_err());
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "submit_unit_tx",
+            "--template",
+            "transfer.json",
+            "--rows",
+            "rows.csv",
+            "--dry-run",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_payout_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "payout",
+            "--csv",
+            "payout.csv",
+            "--result",
+            "result.csv",
+            "--asset",
+            "#MYASSET",
+            "--network",
+            "SKYNET",
+            "--fuel",
+            "1000",
+            "--from",
+            "#TREASURY",
+            "--private-key",
+            "-",
+            "--bulk-size",
+            "10",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_ok());
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "payout",
+            "--csv",
+            "payout.csv",
+            "--result",
+            "result.csv",
+            "--template",
+            "transfer.json",
+            "--fuel",
+            "1000",
+            "--private-key",
+            "-",
+            "--url",
+            "any_url",
+        ]);
+        assert!(command.is_err())
+    }
+    #[test]
+    fn test_node_info_command() {
+        let command =
+            create_app().try_get_matches_from(vec!["prog", "node_info", "--url", "any_url"]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_create_unit_tx_abi_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "create_unit_tx",
+            "--json",
+            "any",
+            "--abi",
+            "abi.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_get_receipt_command() {
+        let command = create_app().try_get_matches_from(vec![
+            "prog",
+            "get_receipt",
+            "--hash",
+            "any",
+            "--url",
+            "any_url",
+            "--abi",
+            "abi.json",
+        ]);
+        assert!(command.is_ok())
+    }
+    #[test]
+    fn test_get_tx_command() {
+        let command = create_app()
+            .try_get_matches_from(vec!["prog", "get_tx", "--hash", "any", "--url", "any_url"]);
+        assert!(command.is_ok())
+    }
 }
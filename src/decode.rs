@@ -0,0 +1,347 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::{json, Value};
+use trinci_core::{
+    base::{
+        schema::{Block, Receipt, SignedTransaction, TransactionData},
+        serialize::rmp_deserialize,
+    },
+    crypto::{Hash, Hashable},
+    Message, Transaction,
+};
+
+use crate::{
+    abi::{self, TypeSchema},
+    encode::{encode_args, BIN_HINT, EXT_HINT, MAP_HINT},
+    types::{Result, UnitTxArgs},
+};
+
+/// Hex representation of a multihash
+pub fn hash_to_hex(hash: &Hash) -> String {
+    hex::encode(hash.as_bytes())
+}
+
+fn rmpv_to_json(value: &rmpv::Value, non_string_keys: &mut bool) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(val) => json!(val),
+        rmpv::Value::Integer(val) => match val.as_u64() {
+            Some(val) => json!(val),
+            None => json!(val.as_i64()),
+        },
+        rmpv::Value::F32(val) => json!(val),
+        rmpv::Value::F64(val) => json!(val),
+        rmpv::Value::String(val) => match val.as_str() {
+            Some(text) => json!(text),
+            None => json!({ BIN_HINT: hex::encode(val.as_bytes()) }),
+        },
+        rmpv::Value::Binary(val) => json!({ BIN_HINT: hex::encode(val) }),
+        rmpv::Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| rmpv_to_json(item, non_string_keys))
+                .collect(),
+        ),
+        rmpv::Value::Map(entries) => {
+            if entries.iter().all(|(key, _)| key.is_str()) {
+                Value::Object(
+                    entries
+                        .iter()
+                        .map(|(key, val)| {
+                            (
+                                key.as_str().unwrap_or_default().to_string(),
+                                rmpv_to_json(val, non_string_keys),
+                            )
+                        })
+                        .collect(),
+                )
+            } else {
+                *non_string_keys = true;
+                let entries = entries
+                    .iter()
+                    .map(|(key, val)| {
+                        json!([
+                            rmpv_to_json(key, non_string_keys),
+                            rmpv_to_json(val, non_string_keys)
+                        ])
+                    })
+                    .collect::<Vec<Value>>();
+                json!({ MAP_HINT: entries })
+            }
+        }
+        rmpv::Value::Ext(typeid, data) => {
+            json!({ EXT_HINT: { "type": typeid, "data": hex::encode(data) } })
+        }
+    }
+}
+
+/// Decode a MessagePack buffer into json.
+/// Binary and ext values are tagged with the same hints accepted by the
+/// args encoder, maps with non-string keys become `{"$map": [[key, value], ...]}`
+/// and are flagged by the returned boolean.
+pub fn msgpack_to_json(buf: &[u8]) -> Result<(Value, bool)> {
+    let mut reader = buf;
+    let value = rmpv::decode::read_value(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(format!(
+            "{} trailing bytes after the messagepack value",
+            reader.len()
+        )
+        .into());
+    }
+
+    let mut non_string_keys = false;
+    let value = rmpv_to_json(&value, &mut non_string_keys);
+    Ok((value, non_string_keys))
+}
+
+/// Decode the messagepacked contract arguments.
+/// If the buffer is not a valid messagepack its hex is returned.
+pub fn args_to_json(args: &[u8]) -> Value {
+    match msgpack_to_json(args) {
+        Ok((value, _)) => value,
+        Err(_) => json!(hex::encode(args)),
+    }
+}
+
+/// Decode the messagepacked contract returns converting them to the declared type
+fn typed_returns_to_json(returns: &[u8], schema: &TypeSchema) -> Result<Value> {
+    let value = rmp_serde::from_slice::<serde_value::Value>(returns)?;
+    let value = abi::coerce(&value, schema, "returns")?;
+    let (value, _) = msgpack_to_json(&encode_args(&value)?)?;
+    Ok(value)
+}
+
+/// Json representation of a receipt.
+/// When the contract method return type is known the returns are decoded with it,
+/// a failed conversion is reported in `returns_error`.
+pub fn receipt_to_json(rx: &Receipt, returns_schema: Option<&TypeSchema>) -> Value {
+    let mut value = json!({
+        "height": rx.height,
+        "index": rx.index,
+        "burned_fuel": rx.burned_fuel,
+        "success": rx.success,
+    });
+
+    // A failed execution returns the error message
+    let returns = match returns_schema {
+        _ if !rx.success => json!(String::from_utf8_lossy(&rx.returns)),
+        Some(schema) => match typed_returns_to_json(&rx.returns, schema) {
+            Ok(returns) => returns,
+            Err(e) => {
+                value["returns_error"] = json!(e.to_string());
+                args_to_json(&rx.returns)
+            }
+        },
+        None => args_to_json(&rx.returns),
+    };
+    value["returns"] = returns;
+    value
+}
+
+/// Json representation of the transaction data
+pub fn tx_data_to_json(data: &TransactionData) -> Value {
+    match data {
+        TransactionData::V1(data) => json!({
+            "account": data.account,
+            "fuel_limit": data.fuel_limit,
+            "nonce": hex::encode(&data.nonce),
+            "network": data.network,
+            "contract": data.contract.as_ref().map(hash_to_hex),
+            "method": data.method,
+            "caller": data.caller.to_account_id(),
+            "args": args_to_json(&data.args),
+        }),
+        _ => json!(format!("{:?}", data)),
+    }
+}
+
+/// Json representation of a signed transaction
+pub fn signed_tx_to_json(tx: &SignedTransaction) -> Value {
+    json!({
+        "hash": hash_to_hex(&tx.data.primary_hash()),
+        "data": tx_data_to_json(&tx.data),
+        "signature": hex::encode(&tx.signature),
+    })
+}
+
+/// Json representation of a transaction
+pub fn transaction_to_json(tx: &Transaction) -> Value {
+    match tx {
+        Transaction::UnitTransaction(tx) => signed_tx_to_json(tx),
+        _ => json!(format!("{:?}", tx)),
+    }
+}
+
+/// Json representation of a block header,
+/// with the transactions hashes when available
+pub fn block_to_json(block: &Block, txs: Option<&Vec<Hash>>) -> Value {
+    let data = &block.data;
+    json!({
+        "height": data.height,
+        "size": data.size,
+        "timestamp": data.timestamp,
+        "validator": data.validator.as_ref().map(|pk| pk.to_account_id()),
+        "prev_hash": hash_to_hex(&data.prev_hash),
+        "txs_hash": hash_to_hex(&data.txs_hash),
+        "rxs_hash": hash_to_hex(&data.rxs_hash),
+        "state_hash": hash_to_hex(&data.state_hash),
+        "signature": hex::encode(&block.signature),
+        "txs": txs.map(|txs| txs.iter().map(hash_to_hex).collect::<Vec<String>>()),
+    })
+}
+
+/// Json representation of the unit tx args, accepted back as `--json` input.
+/// The contract args are converted with the typed hints.
+pub fn unit_tx_args_to_json(args: &UnitTxArgs) -> Result<Value> {
+    let mut value = serde_json::to_value(args)?;
+    let (contract_args, _) = msgpack_to_json(&encode_args(&args.args)?)?;
+    value["args"] = contract_args;
+    Ok(value)
+}
+
+/// Outcome of a transaction submission from the node response body,
+/// `OK|<hash>` or `KO|<error>`
+pub fn tx_response_outcome(buf: &[u8]) -> Result<String> {
+    let output = match buf {
+        b"true" => String::from("OK|Valid Transaction!"),
+        b"false" => String::from("KO|Invalid Transaction!"),
+        _ => match rmp_deserialize::<Message>(buf)? {
+            Message::PutTransactionResponse { hash } => format!("OK|{}", hash_to_hex(&hash)),
+            Message::Exception(e) => format!("KO|{:?}", e.kind),
+            msg => format!("KO|{:?}", msg),
+        },
+    };
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_tx_args_to_json_typed_args() {
+        let args = UnitTxArgs {
+            target: String::from("#ACCOUNT"),
+            network: String::from("SKYNET"),
+            fuel: 1000,
+            contract: String::new(),
+            method: String::from("store"),
+            args: serde_value::Value::Bytes(vec![1, 2, 255]),
+            private_key: Default::default(),
+        };
+
+        let res = unit_tx_args_to_json(&args).unwrap();
+
+        assert_eq!(res["args"], json!({ "$bin": "0102ff" }));
+        assert_eq!(res["method"], json!("store"));
+    }
+
+    #[test]
+    fn tx_response_outcome_confirm() {
+        assert_eq!(
+            tx_response_outcome(b"true").unwrap(),
+            "OK|Valid Transaction!"
+        );
+        assert_eq!(
+            tx_response_outcome(b"false").unwrap(),
+            "KO|Invalid Transaction!"
+        );
+    }
+
+    #[test]
+    fn tx_response_outcome_garbage() {
+        assert!(tx_response_outcome(&[0xc1, 0xff, 0x00]).is_err());
+    }
+
+    #[test]
+    fn args_to_json_msgpack() {
+        // {"a":1,"b":"text"}
+        let buf = [130, 161, 97, 1, 161, 98, 164, 116, 101, 120, 116];
+
+        let res = args_to_json(&buf);
+
+        assert_eq!(res, json!({"a": 1, "b": "text"}));
+    }
+
+    #[test]
+    fn msgpack_to_json_tagged_values() {
+        // {"bin": bin[1, 2], "ext": ext(3, [10, 11]), "map": {1: true}}
+        let buf = [
+            131, 163, 98, 105, 110, 196, 2, 1, 2, 163, 101, 120, 116, 213, 3, 10, 11, 163, 109, 97,
+            112, 129, 1, 195,
+        ];
+
+        let (res, non_string_keys) = msgpack_to_json(&buf).unwrap();
+
+        assert_eq!(
+            res,
+            json!({
+                "bin": {"$bin": "0102"},
+                "ext": {"$ext": {"type": 3, "data": "0a0b"}},
+                "map": {"$map": [[1, true]]},
+            })
+        );
+        assert!(non_string_keys);
+    }
+
+    #[test]
+    fn msgpack_to_json_round_trip() {
+        let buf = [
+            131, 163, 98, 105, 110, 196, 2, 1, 2, 163, 101, 120, 116, 213, 3, 10, 11, 163, 109, 97,
+            112, 129, 1, 195,
+        ];
+        let (value, _) = msgpack_to_json(&buf).unwrap();
+
+        let res = crate::encode::encode_json(value).unwrap();
+
+        assert_eq!(res, buf);
+    }
+
+    #[test]
+    fn msgpack_to_json_trailing_bytes() {
+        let buf = [1, 2];
+
+        assert!(msgpack_to_json(&buf).is_err());
+    }
+
+    #[test]
+    fn typed_returns() {
+        // {"hash": "0102"}
+        let buf = [129, 164, 104, 97, 115, 104, 164, 48, 49, 48, 50];
+        let mut fields = std::collections::BTreeMap::new();
+        fields.insert("hash".to_string(), TypeSchema::Bin);
+        let schema = TypeSchema::Map {
+            fields,
+            optional: vec![],
+        };
+
+        let res = typed_returns_to_json(&buf, &schema).unwrap();
+
+        assert_eq!(res, json!({"hash": {"$bin": "0102"}}));
+    }
+
+    #[test]
+    fn args_to_json_invalid_msgpack() {
+        let buf = [0xc1];
+
+        let res = args_to_json(&buf);
+
+        assert_eq!(res, json!("c1"));
+    }
+}
@@ -0,0 +1,275 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Contract arguments encoder.
+//!
+//! Values are encoded as `rmp_serialize` does, except for the single entry
+//! maps with one of the following keys, that are encoded as the exact
+//! MessagePack type:
+//!  - `{"$bin": "<hex>"}`: bin
+//!  - `{"$bs58": "<base58>"}`: bin
+//!  - `{"$u64": "<integer>"}`: unsigned integer
+//!  - `{"$i64": "<integer>"}`: signed integer
+//!  - `{"$ext": {"type": <i8>, "data": "<hex>"}}`: ext
+//!  - `{"$map": [[<key>, <value>], ...]}`: map, also with non-string keys
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_value::Value;
+
+use crate::types::Result;
+
+pub const BIN_HINT: &str = "$bin";
+pub const BS58_HINT: &str = "$bs58";
+pub const U64_HINT: &str = "$u64";
+pub const I64_HINT: &str = "$i64";
+pub const EXT_HINT: &str = "$ext";
+pub const MAP_HINT: &str = "$map";
+
+/// Encode the contract arguments in MessagePack
+pub fn encode_args(value: &Value) -> Result<Vec<u8>> {
+    let mut buf = vec![];
+    write_value(&mut buf, value)?;
+    Ok(buf)
+}
+
+/// Encode a json value in MessagePack, with the same rules of the contract arguments
+pub fn encode_json(value: serde_json::Value) -> Result<Vec<u8>> {
+    encode_args(&serde_value::to_value(value)?)
+}
+
+fn hint_text<'a>(hint: &str, value: &'a Value) -> Result<&'a str> {
+    match value {
+        Value::String(text) => Ok(text),
+        _ => Err(format!("`{}` value must be a string", hint).into()),
+    }
+}
+
+fn hint_integer<T>(hint: &str, value: &Value) -> Result<T>
+where
+    T: FromStr + TryFrom<u64> + TryFrom<i64>,
+{
+    let res = match value {
+        Value::String(text) => T::from_str(text).ok(),
+        Value::U64(val) => T::try_from(*val).ok(),
+        Value::I64(val) => T::try_from(*val).ok(),
+        _ => None,
+    };
+    res.ok_or_else(|| format!("`{}` value is not a valid integer", hint).into())
+}
+
+fn write_ext(buf: &mut Vec<u8>, value: &Value) -> Result<()> {
+    let (typeid, data) = match value {
+        Value::Map(map) => (
+            map.get(&Value::String("type".to_string())),
+            map.get(&Value::String("data".to_string())),
+        ),
+        _ => (None, None),
+    };
+    let (typeid, data) = match (typeid, data) {
+        (Some(typeid), Some(data)) => (
+            hint_integer::<i8>(EXT_HINT, typeid)?,
+            hex::decode(hint_text(EXT_HINT, data)?)?,
+        ),
+        _ => return Err(format!("`{}` value must be {{\"type\", \"data\"}}", EXT_HINT).into()),
+    };
+    rmp::encode::write_ext_meta(buf, data.len() as u32, typeid)?;
+    buf.extend_from_slice(&data);
+    Ok(())
+}
+
+fn write_map_entries(buf: &mut Vec<u8>, value: &Value) -> Result<()> {
+    let entries = match value {
+        Value::Seq(entries) => entries,
+        _ => return Err(format!("`{}` value must be an array", MAP_HINT).into()),
+    };
+    rmp::encode::write_map_len(buf, entries.len() as u32)?;
+    for entry in entries {
+        match entry {
+            Value::Seq(pair) if pair.len() == 2 => {
+                write_value(buf, &pair[0])?;
+                write_value(buf, &pair[1])?;
+            }
+            _ => return Err(format!("`{}` entries must be [key, value]", MAP_HINT).into()),
+        }
+    }
+    Ok(())
+}
+
+/// Write the typed value if the map is a type hint.
+/// Returns `false` if the map is a regular one.
+fn write_hint(buf: &mut Vec<u8>, map: &BTreeMap<Value, Value>) -> Result<bool> {
+    let (hint, value) = match map.iter().next() {
+        Some((Value::String(hint), value)) if map.len() == 1 => (hint.as_str(), value),
+        _ => return Ok(false),
+    };
+
+    match hint {
+        BIN_HINT => rmp::encode::write_bin(buf, &hex::decode(hint_text(hint, value)?)?)?,
+        BS58_HINT => {
+            let bytes = bs58::decode(hint_text(hint, value)?).into_vec()?;
+            rmp::encode::write_bin(buf, &bytes)?
+        }
+        U64_HINT => {
+            rmp::encode::write_uint(buf, hint_integer::<u64>(hint, value)?)?;
+        }
+        I64_HINT => {
+            rmp::encode::write_sint(buf, hint_integer::<i64>(hint, value)?)?;
+        }
+        EXT_HINT => write_ext(buf, value)?,
+        MAP_HINT => write_map_entries(buf, value)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn write_value(buf: &mut Vec<u8>, value: &Value) -> Result<()> {
+    match value {
+        Value::Bool(val) => rmp::encode::write_bool(buf, *val)?,
+        Value::U8(val) => {
+            rmp::encode::write_uint(buf, *val as u64)?;
+        }
+        Value::U16(val) => {
+            rmp::encode::write_uint(buf, *val as u64)?;
+        }
+        Value::U32(val) => {
+            rmp::encode::write_uint(buf, *val as u64)?;
+        }
+        Value::U64(val) => {
+            rmp::encode::write_uint(buf, *val)?;
+        }
+        Value::I8(val) => {
+            rmp::encode::write_sint(buf, *val as i64)?;
+        }
+        Value::I16(val) => {
+            rmp::encode::write_sint(buf, *val as i64)?;
+        }
+        Value::I32(val) => {
+            rmp::encode::write_sint(buf, *val as i64)?;
+        }
+        Value::I64(val) => {
+            rmp::encode::write_sint(buf, *val)?;
+        }
+        Value::F32(val) => rmp::encode::write_f32(buf, *val)?,
+        Value::F64(val) => rmp::encode::write_f64(buf, *val)?,
+        Value::Char(val) => rmp::encode::write_str(buf, &val.to_string())?,
+        Value::String(val) => rmp::encode::write_str(buf, val)?,
+        Value::Unit | Value::Option(None) => rmp::encode::write_nil(buf)?,
+        Value::Option(Some(val)) | Value::Newtype(val) => write_value(buf, val)?,
+        Value::Seq(seq) => {
+            rmp::encode::write_array_len(buf, seq.len() as u32)?;
+            for item in seq {
+                write_value(buf, item)?;
+            }
+        }
+        Value::Map(map) => {
+            if !write_hint(buf, map)? {
+                rmp::encode::write_map_len(buf, map.len() as u32)?;
+                for (key, val) in map {
+                    write_value(buf, key)?;
+                    write_value(buf, val)?;
+                }
+            }
+        }
+        Value::Bytes(bytes) => rmp::encode::write_bin(buf, bytes)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trinci_core::base::serialize::rmp_serialize;
+
+    fn json_to_value(json_text: &str) -> Value {
+        serde_json::from_str(json_text).unwrap()
+    }
+
+    #[test]
+    fn encode_plain_args() {
+        let value = json_to_value(r#"{"a":1,"b":"text","c":[1,-2,3.5],"d":null,"e":true}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, rmp_serialize(&value).unwrap());
+    }
+
+    #[test]
+    fn encode_bin_hint() {
+        let value = json_to_value(r#"{"hash":{"$bin":"0102ff"}}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, [129, 164, 104, 97, 115, 104, 196, 3, 1, 2, 255]);
+    }
+
+    #[test]
+    fn encode_bs58_hint() {
+        let value = json_to_value(r#"{"$bs58":"5T"}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, [196, 2, 1, 2]);
+    }
+
+    #[test]
+    fn encode_integer_hints() {
+        let value = json_to_value(r#"[{"$u64":"18446744073709551615"},{"$i64":"-1"},{"$u64":5}]"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(
+            res,
+            [147, 207, 255, 255, 255, 255, 255, 255, 255, 255, 255, 5]
+        );
+    }
+
+    #[test]
+    fn encode_ext_hint() {
+        let value = json_to_value(r#"{"$ext":{"type":3,"data":"0a0b"}}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, [213, 3, 10, 11]);
+    }
+
+    #[test]
+    fn encode_map_hint() {
+        let value = json_to_value(r#"{"$map":[[1,true],["a",null]]}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, [130, 1, 195, 161, 97, 192]);
+    }
+
+    #[test]
+    fn encode_not_hint_map() {
+        let value = json_to_value(r#"{"$bin":"00","other":1}"#);
+
+        let res = encode_args(&value).unwrap();
+
+        assert_eq!(res, rmp_serialize(&value).unwrap());
+    }
+
+    #[test]
+    fn encode_invalid_hint() {
+        let value = json_to_value(r#"{"$bin":"not hex"}"#);
+
+        assert!(encode_args(&value).is_err());
+    }
+}
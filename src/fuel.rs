@@ -0,0 +1,261 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fuel limit selection.
+//!
+//! The fuel burned by the executed transactions is recorded in a json lines
+//! history file, one entry per receipt. The automatic limit is the highest fuel
+//! burned by the latest successful executions of the same contract method,
+//! increased by a safety margin and capped.
+//!
+//! The TRINCI node messages have no dry execution request, the history is the
+//! only source of the estimates.
+
+use std::{fmt, fs::OpenOptions, io::Write};
+
+use serde::{Deserialize, Serialize};
+use trinci_core::base::schema::Receipt;
+
+use crate::types::{Result, UnitTxArgs};
+
+/// Successful executions considered by the estimate, the latest ones
+pub const HISTORY_WINDOW: usize = 20;
+
+/// Safety margin of the automatic limit, percent of the burned fuel
+pub const DEFAULT_FUEL_MARGIN: u64 = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FuelRecord {
+    /// Contract multihash HEX, empty for the account default one
+    pub contract: String,
+    pub method: String,
+    pub burned_fuel: u64,
+    pub success: bool,
+}
+
+impl FuelRecord {
+    pub fn new(contract: &str, method: &str, rx: &Receipt) -> Self {
+        FuelRecord {
+            contract: contract.to_lowercase(),
+            method: method.to_string(),
+            burned_fuel: rx.burned_fuel,
+            success: rx.success,
+        }
+    }
+}
+
+/// Load the history, empty if the file is missing
+pub fn load_history(path: &str) -> Result<Vec<FuelRecord>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("invalid fuel history entry {}: {}", i + 1, e).into())
+        })
+        .collect()
+}
+
+/// Append the record to the history, creating the file if missing
+pub fn record(path: &str, record: &FuelRecord) -> Result<()> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Fuel limit requested in place of the args one
+#[derive(Debug, Clone, PartialEq)]
+pub enum FuelSetting {
+    Fixed(u64),
+    /// Estimated from the history
+    Auto {
+        /// Percent added to the burned fuel
+        margin: u64,
+        /// Highest limit, the args fuel if not given
+        cap: Option<u64>,
+    },
+}
+
+/// Chosen fuel limit and how it was chosen
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuelChoice {
+    pub fuel: u64,
+    pub reason: String,
+}
+
+impl fmt::Display for FuelChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.fuel, self.reason)
+    }
+}
+
+impl FuelSetting {
+    pub fn choose(&self, history: &[FuelRecord], args: &UnitTxArgs) -> FuelChoice {
+        let (margin, cap) = match self {
+            FuelSetting::Fixed(fuel) => {
+                return FuelChoice {
+                    fuel: *fuel,
+                    reason: String::from("given"),
+                }
+            }
+            FuelSetting::Auto { margin, cap } => (*margin, cap.unwrap_or(args.fuel)),
+        };
+
+        let contract = args.contract.to_lowercase();
+        let burned: Vec<u64> = history
+            .iter()
+            .rev()
+            .filter(|record| {
+                record.success && record.contract == contract && record.method == args.method
+            })
+            .take(HISTORY_WINDOW)
+            .map(|record| record.burned_fuel)
+            .collect();
+
+        let (fuel, reason) = match burned.iter().max() {
+            Some(max) => {
+                // Rounded up, never below 1
+                let extra = (u128::from(*max) * u128::from(margin) + 99) / 100;
+                let fuel = u64::try_from(u128::from(*max) + extra).unwrap_or(u64::MAX);
+                (
+                    fuel.max(1),
+                    format!(
+                        "max burned {} in the last {} executions, margin {}%",
+                        max,
+                        burned.len(),
+                        margin
+                    ),
+                )
+            }
+            None => (cap, String::from("no history, the cap")),
+        };
+        if fuel > cap {
+            FuelChoice {
+                fuel: cap,
+                reason: format!("{}, capped at {}", reason, cap),
+            }
+        } else {
+            FuelChoice { fuel, reason }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::PrivateKey;
+    use serde_value::Value;
+
+    const CONTRACT: &str = "12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118";
+
+    fn args(fuel: u64) -> UnitTxArgs {
+        UnitTxArgs {
+            target: String::from("#ACCOUNT"),
+            network: String::from("SKYNET"),
+            fuel,
+            contract: CONTRACT.to_string(),
+            method: String::from("transfer"),
+            args: Value::Unit,
+            private_key: PrivateKey::default(),
+        }
+    }
+
+    fn record(method: &str, burned_fuel: u64, success: bool) -> FuelRecord {
+        FuelRecord {
+            contract: CONTRACT.to_string(),
+            method: method.to_string(),
+            burned_fuel,
+            success,
+        }
+    }
+
+    #[test]
+    fn auto_fuel_from_history() {
+        let history = vec![
+            record("transfer", 900, true),
+            record("transfer", 1000, true),
+            record("transfer", 5000, false),
+            record("mint", 3000, true),
+        ];
+        let setting = FuelSetting::Auto {
+            margin: 20,
+            cap: None,
+        };
+
+        let res = setting.choose(&history, &args(10000));
+
+        assert_eq!(res.fuel, 1200);
+        assert_eq!(
+            res.to_string(),
+            "1200 (max burned 1000 in the last 2 executions, margin 20%)"
+        );
+    }
+
+    #[test]
+    fn auto_fuel_capped() {
+        let history = vec![record("transfer", 1000, true)];
+        let setting = FuelSetting::Auto {
+            margin: 50,
+            cap: Some(1100),
+        };
+
+        let res = setting.choose(&history, &args(10000));
+
+        assert_eq!(res.fuel, 1100);
+        assert!(res.reason.ends_with("capped at 1100"));
+    }
+
+    #[test]
+    fn auto_fuel_without_history() {
+        let setting = FuelSetting::Auto {
+            margin: 20,
+            cap: None,
+        };
+
+        let res = setting.choose(&[record("mint", 10, true)], &args(10000));
+
+        assert_eq!(res.fuel, 10000);
+    }
+
+    #[test]
+    fn history_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "trinci_sign_{}_fuel_history.jsonl",
+            std::process::id()
+        ));
+        let path = path.to_string_lossy().to_string();
+        std::fs::remove_file(&path).unwrap_or_default();
+
+        super::record(&path, &record("transfer", 900, true)).unwrap();
+        super::record(&path, &record("mint", 50, false)).unwrap();
+        let res = load_history(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap_or_default();
+        assert_eq!(
+            res,
+            vec![record("transfer", 900, true), record("mint", 50, false)]
+        );
+    }
+}
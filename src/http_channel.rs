@@ -53,6 +53,20 @@ impl HttpChannel {
         }
     }
 
+    pub fn get(&self, path: &str) -> Result<Vec<u8>> {
+        let url = self.url.to_string() + path;
+        let mut body = vec![];
+
+        match ureq::get(&url).call() {
+            Ok(res) => {
+                res.into_reader().read_to_end(&mut body)?;
+                Ok(body)
+            }
+            Err(Error::Status(code, _res)) => Err(format!("GET error {}", code).into()),
+            Err(err) => Err(format!("GET error {}", err).into()),
+        }
+    }
+
     pub fn recv(&mut self) -> Result<Vec<u8>> {
         match self.res.take() {
             Some(Ok(buf)) => Ok(buf),
@@ -0,0 +1,41 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transactions building and signing, shared by the `trinci-sign` binary
+//! and the fuzz targets.
+
+pub mod abi;
+pub mod asset;
+pub mod audit;
+pub mod commands;
+pub mod decode;
+pub mod encode;
+pub mod fuel;
+pub mod http_channel;
+pub mod mock_node;
+pub mod multisig;
+pub mod payout;
+pub mod pkcs11;
+pub mod policy;
+pub mod remote_signer;
+pub mod secret;
+pub mod signer;
+pub mod template;
+pub mod types;
+pub mod validation;
+pub mod vectors;
+pub mod wizard;
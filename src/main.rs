@@ -17,138 +17,27 @@
 
 use std::io::{self, Write};
 
-use common::{bs58_into_vec, get_args};
-use http_channel::HttpChannel;
-use trinci_core::{
-    base::{
-        schema::{SignedTransaction, TransactionData},
-        serialize::{rmp_deserialize, rmp_serialize, MessagePack},
-    },
-    crypto::{
-        ecdsa::{CurveId, KeyPair as EcdsaKeyPair},
-        Hash,
-    },
-    KeyPair, Message, TransactionDataV1,
+use common::get_args;
+use trinci_sign::{
+    commands,
+    types::{AppOperation, Arguments},
 };
-use types::{AppOperation, Arguments, Result};
 
 mod common;
-mod http_channel;
-mod types;
-
-fn submit_unit_tx(input_args: Arguments, url: String) -> Result<()> {
-    let tx = create_unit_tx_as_vec(input_args)?;
-    let mut http_channel = HttpChannel::new(url);
-    http_channel.send(tx)?;
-    let buf = http_channel.recv()?;
-
-    let output = if String::from_utf8_lossy(&buf) == *"true" {
-        String::from("OK|Valid Transaction!")
-    } else if String::from_utf8_lossy(&buf) == *"false" {
-        String::from("KO|Invalid Transaction!")
-    } else {
-        let msg = rmp_deserialize::<Message>(&buf)?;
-
-        match msg {
-            Message::PutTransactionResponse { hash } => {
-                format!("OK|{}", hex::encode(hash.as_bytes()))
-            }
-            Message::Exception(e) => {
-                format!("KO|{:?}", e.kind)
-            }
-            _ => {
-                format!("KO|{:?}", msg)
-            }
-        }
-    };
-    io::stdout()
-        .write_all(output.as_bytes())
-        .unwrap_or_default();
-
-    Ok(())
-}
-
-fn create_unit_tx_as_vec(input_args: Arguments) -> Result<Vec<u8>> {
-    match input_args {
-        Arguments::UnitTxArgsType(input_args) => {
-            let contract = if input_args.contract.is_empty() {
-                None
-            } else {
-                Hash::from_hex(&input_args.contract).ok()
-            };
-
-            let private_bytes = bs58_into_vec(&input_args.private_key)?;
-
-            let kp = EcdsaKeyPair::from_pkcs8_bytes(CurveId::Secp384R1, &private_bytes)?;
-
-            let args = rmp_serialize(&input_args.args)?;
-
-            let nonce = rand::random::<u64>().to_be_bytes().to_vec();
-
-            let data = TransactionDataV1 {
-                account: input_args.target,
-                fuel_limit: input_args.fuel,
-                nonce,
-                network: input_args.network,
-                contract,
-                method: input_args.method,
-                caller: trinci_core::PublicKey::Ecdsa(kp.public_key()),
-                args,
-            };
-
-            let data = TransactionData::V1(data);
-            let bytes = data.serialize();
-            let signature = KeyPair::Ecdsa(kp).sign(&bytes)?;
-
-            let sign_tx = SignedTransaction { data, signature };
-
-            let tx = trinci_core::Transaction::UnitTransaction(sign_tx);
-
-            let message = Message::PutTransactionRequest { confirm: true, tx };
-
-            // Message pack of the transaction
-            let buf = rmp_serialize(&message)?;
-
-            Ok(buf)
-        }
-        _ => panic!("unexpected value"),
-    }
-}
-
-fn convert_string_to_msgpack(input_args: String) -> Result<()> {
-    let args = rmp_serialize(&input_args)?;
-    let value = format!("{:?}", args).replace(' ', "");
-    io::stdout().write_all(value.as_bytes()).unwrap_or_default();
-
-    Ok(())
-}
-fn convert_json_struct_to_msgpack(input_args: serde_json::Value) -> Result<()> {
-    let args = rmp_serialize(&input_args)?;
-    let value = format!("{:?}", args).replace(' ', "");
-    io::stdout().write_all(value.as_bytes()).unwrap_or_default();
-
-    Ok(())
-}
-
-fn create_unit_tx(input_args: Arguments) -> Result<()> {
-    let tx = create_unit_tx_as_vec(input_args)?;
-    io::stdout().write_all(&tx).unwrap_or_default();
-    Ok(())
-}
 
 fn main() {
     let args = get_args();
     match args {
         Some(cmd) => match cmd.operation {
             AppOperation::CreateUnitTx => {
-                if let Err(e) = create_unit_tx(cmd.args) {
+                if let Err(e) = commands::create_unit_tx(cmd.args, cmd.options) {
                     io::stdout()
                         .write_all(format!("KO|Error creating unit tx message {:?}", e).as_bytes())
                         .unwrap_or_default();
                 }
             }
             AppOperation::SubmitUnitTx => {
-                if let Err(e) = submit_unit_tx(cmd.args, cmd.url) {
+                if let Err(e) = commands::submit_unit_tx(cmd.args, cmd.url, cmd.options) {
                     io::stdout()
                         .write_all(format!("KO|Error sending unit tx message {:?}", e).as_bytes())
                         .unwrap_or_default();
@@ -156,7 +45,7 @@ fn main() {
             }
             AppOperation::ToMessagePack => match cmd.args {
                 Arguments::MsgPackString(val) => {
-                    if let Err(e) = convert_string_to_msgpack(val) {
+                    if let Err(e) = commands::convert_string_to_msgpack(val, cmd.options) {
                         io::stdout()
                             .write_all(
                                 format!("KO|converting the string into msgpack {:?}", e).as_bytes(),
@@ -165,7 +54,9 @@ fn main() {
                     }
                 }
                 Arguments::MsgPackStruct(json_struct) => {
-                    if let Err(e) = convert_json_struct_to_msgpack(json_struct) {
+                    if let Err(e) =
+                        commands::convert_json_struct_to_msgpack(json_struct, cmd.options)
+                    {
                         io::stdout()
                             .write_all(
                                 format!("KO|converting the json structure into msgpack {:?}", e)
@@ -174,8 +65,126 @@ fn main() {
                             .unwrap_or_default();
                     }
                 }
-                Arguments::UnitTxArgsType(_) => panic!("unexpected value"),
+                _ => {
+                    io::stdout()
+                        .write_all("KO|unexpected arguments".as_bytes())
+                        .unwrap_or_default();
+                }
             },
+            AppOperation::FromMessagePack => match cmd.args {
+                Arguments::MsgPackBytes(buf) => {
+                    if let Err(e) = commands::convert_msgpack_to_json(buf) {
+                        io::stdout()
+                            .write_all(
+                                format!("KO|converting the msgpack into json {:?}", e).as_bytes(),
+                            )
+                            .unwrap_or_default();
+                    }
+                }
+                _ => {
+                    io::stdout()
+                        .write_all("KO|unexpected arguments".as_bytes())
+                        .unwrap_or_default();
+                }
+            },
+            AppOperation::GetBlock => {
+                if let Err(e) = commands::get_block(cmd.args, cmd.url) {
+                    io::stdout()
+                        .write_all(format!("KO|Error retrieving block {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::GetReceipt => {
+                if let Err(e) = commands::get_receipt(cmd.args, cmd.url, cmd.options) {
+                    io::stdout()
+                        .write_all(format!("KO|Error retrieving receipt {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Multisig => {
+                if let Err(e) = commands::multisig(cmd.args, cmd.url, cmd.options) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in multisig operation {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Audit => {
+                if let Err(e) = commands::audit_verify(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error verifying the audit log {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::SignerServer => {
+                if let Err(e) = commands::signer_server(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in signer server {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::EncodeArgs => {
+                if let Err(e) = commands::encode_unit_tx_args(cmd.args, cmd.options) {
+                    io::stdout()
+                        .write_all(format!("KO|Error encoding the args {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::DecodeArgs => {
+                if let Err(e) = commands::decode_unit_tx_args(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error decoding the args {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Wizard => {
+                if let Err(e) = commands::wizard(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in wizard {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Repl => {
+                if let Err(e) = commands::repl(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in repl {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Payout => {
+                if let Err(e) = commands::payout(cmd.args, cmd.url, cmd.options) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in payout {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::Selftest => {
+                if let Err(e) = commands::selftest(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in selftest {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::MockNode => {
+                if let Err(e) = commands::mock_node(cmd.args) {
+                    io::stdout()
+                        .write_all(format!("KO|Error in mock node {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::NodeInfo => {
+                if let Err(e) = commands::node_info(cmd.url) {
+                    io::stdout()
+                        .write_all(format!("KO|Error retrieving node info {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
+            AppOperation::GetTx => {
+                if let Err(e) = commands::get_tx(cmd.args, cmd.url) {
+                    io::stdout()
+                        .write_all(format!("KO|Error retrieving transaction {:?}", e).as_bytes())
+                        .unwrap_or_default();
+                }
+            }
         },
         None => {
             eprintln!("Error reading args!");
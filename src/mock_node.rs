@@ -0,0 +1,418 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mock TRINCI node for offline tests.
+//!
+//! Serves `POST /message` decoding the `Message`s. The `PutTransactionRequest`s
+//! are answered with the scripted replies, in order, then accepted. The accepted
+//! transactions and their receipts are stored and served by the
+//! `GetTransactionRequest` and `GetReceiptRequest`.
+//!
+//! `MockNode` serves on a local port in a background thread for the tests,
+//! the `mock_node` subcommand serves in the foreground.
+//!
+//! The scenario file is a json array of replies:
+//! ```json
+//! [
+//!     { "reply": "accept", "success": true, "returns": "c0" },
+//!     { "reply": "exception", "kind": "duplicated_unconfirmed_tx" },
+//!     { "reply": "true" },
+//!     { "reply": "false" },
+//!     { "reply": "http_error", "status": 500, "body": "internal error" },
+//!     { "reply": "http_error", "status": 504, "accepted": true }
+//! ]
+//! ```
+//! An `http_error` with `accepted` executes the transaction before failing,
+//! as a node answering after the client gave up.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Read,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Response, Server};
+use trinci_core::{
+    base::{
+        schema::{Receipt, SignedTransaction, TransactionData},
+        serialize::{rmp_deserialize, rmp_serialize},
+    },
+    crypto::{
+        ecdsa::{CurveId, PublicKey as EcdsaPublicKey},
+        Hash, Hashable,
+    },
+    Error as CoreError, ErrorKind, Message, PublicKey, Transaction, TransactionDataV1,
+};
+
+use crate::types::Result;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExceptionKind {
+    InvalidSignature,
+    DuplicatedUnconfirmedTx,
+    ResourceNotFound,
+    Other,
+}
+
+impl From<ExceptionKind> for ErrorKind {
+    fn from(kind: ExceptionKind) -> Self {
+        match kind {
+            ExceptionKind::InvalidSignature => ErrorKind::InvalidSignature,
+            ExceptionKind::DuplicatedUnconfirmedTx => ErrorKind::DuplicatedUnconfirmedTx,
+            ExceptionKind::ResourceNotFound => ErrorKind::ResourceNotFound,
+            ExceptionKind::Other => ErrorKind::Other,
+        }
+    }
+}
+
+fn default_success() -> bool {
+    true
+}
+
+/// Reply to a transaction submission
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "reply", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Reply {
+    /// `PutTransactionResponse`, with the receipt of the execution
+    Accept {
+        #[serde(default = "default_success")]
+        success: bool,
+        /// Returns HEX
+        #[serde(default)]
+        returns: String,
+    },
+    /// `Exception` message
+    Exception { kind: ExceptionKind },
+    /// `true` body
+    True,
+    /// `false` body
+    False,
+    /// Non 200 HTTP status
+    HttpError {
+        status: u16,
+        #[serde(default)]
+        body: String,
+        /// The transaction is accepted anyway
+        #[serde(default)]
+        accepted: bool,
+    },
+}
+
+impl Reply {
+    pub fn accept() -> Self {
+        Reply::Accept {
+            success: true,
+            returns: String::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct NodeState {
+    scenario: VecDeque<Reply>,
+    txs: HashMap<Hash, Transaction>,
+    receipts: HashMap<Hash, Receipt>,
+    received: usize,
+}
+
+fn exception(kind: ErrorKind) -> Message {
+    Message::Exception(CoreError::new(kind))
+}
+
+impl NodeState {
+    fn put_transaction(&mut self, tx: Transaction) -> (u16, Vec<u8>) {
+        let reply = self.scenario.pop_front().unwrap_or_else(Reply::accept);
+        let message = match reply {
+            Reply::Accept { success, returns } => {
+                let hash = self.execute(tx, success, &returns);
+                Message::PutTransactionResponse { hash }
+            }
+            Reply::Exception { kind } => exception(kind.into()),
+            Reply::True => return (200, b"true".to_vec()),
+            Reply::False => return (200, b"false".to_vec()),
+            Reply::HttpError {
+                status,
+                body,
+                accepted,
+            } => {
+                if accepted {
+                    self.execute(tx, true, "");
+                }
+                return (status, body.into_bytes());
+            }
+        };
+        message_reply(&message)
+    }
+
+    /// Store the transaction and its receipt, returning its hash
+    fn execute(&mut self, tx: Transaction, success: bool, returns: &str) -> Hash {
+        let hash = match &tx {
+            Transaction::UnitTransaction(sign_tx) => sign_tx.data.primary_hash(),
+            Transaction::BulkTransaction(bulk_tx) => bulk_tx.data.primary_hash(),
+        };
+        let receipt = Receipt {
+            height: 0,
+            index: self.receipts.len() as u32,
+            burned_fuel: 0,
+            success,
+            returns: hex::decode(returns).unwrap_or_default(),
+        };
+        self.txs.insert(hash, tx);
+        self.receipts.insert(hash, receipt);
+        hash
+    }
+
+    fn handle(&mut self, body: &[u8]) -> (u16, Vec<u8>) {
+        self.received += 1;
+        let message = match rmp_deserialize::<Message>(body) {
+            Ok(message) => message,
+            Err(_) => return message_reply(&exception(ErrorKind::MalformedData)),
+        };
+        let reply = match message {
+            Message::PutTransactionRequest { tx, .. } => return self.put_transaction(tx),
+            Message::GetTransactionRequest { hash } => match self.txs.get(&hash) {
+                Some(tx) => Message::GetTransactionResponse { tx: tx.clone() },
+                None => exception(ErrorKind::ResourceNotFound),
+            },
+            Message::GetReceiptRequest { hash } => match self.receipts.get(&hash) {
+                Some(rx) => Message::GetReceiptResponse { rx: rx.clone() },
+                None => exception(ErrorKind::ResourceNotFound),
+            },
+            _ => exception(ErrorKind::Other),
+        };
+        message_reply(&reply)
+    }
+}
+
+fn message_reply(message: &Message) -> (u16, Vec<u8>) {
+    match rmp_serialize(message) {
+        Ok(buf) => (200, buf),
+        Err(e) => (500, e.to_string().into_bytes()),
+    }
+}
+
+fn run(server: &Server, state: &Mutex<NodeState>) {
+    for mut request in server.incoming_requests() {
+        let mut body = vec![];
+        let (status, buf) = match request.as_reader().read_to_end(&mut body) {
+            Ok(_) if request.url() == "/message" => match state.lock() {
+                Ok(mut state) => state.handle(&body),
+                Err(_) => (500, b"poisoned state".to_vec()),
+            },
+            Ok(_) => (404, b"not found".to_vec()),
+            Err(e) => (400, e.to_string().into_bytes()),
+        };
+        let response = Response::from_data(buf).with_status_code(status);
+        if let Err(e) = request.respond(response) {
+            eprintln!("Error answering the request: {}", e);
+        }
+    }
+}
+
+/// Mock node serving on a local port in a background thread,
+/// stopped on drop
+pub struct MockNode {
+    server: Arc<Server>,
+    state: Arc<Mutex<NodeState>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockNode {
+    pub fn start(scenario: Vec<Reply>) -> Result<Self> {
+        let server = Arc::new(Server::http("127.0.0.1:0")?);
+        let state = Arc::new(Mutex::new(NodeState {
+            scenario: scenario.into(),
+            ..Default::default()
+        }));
+
+        let thread = {
+            let server = server.clone();
+            let state = state.clone();
+            std::thread::spawn(move || run(&server, &state))
+        };
+        Ok(MockNode {
+            server,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    /// Node url, as given to the subcommands
+    pub fn url(&self) -> String {
+        format!("http://{}", self.server.server_addr())
+    }
+
+    /// Number of messages received
+    pub fn received(&self) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.received)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap_or_default();
+        }
+    }
+}
+
+/// Unit transaction with a dummy signature, accepted by the mock node
+/// that does not verify it
+pub fn dummy_transaction() -> Transaction {
+    let data = TransactionDataV1 {
+        account: String::from("#ACCOUNT"),
+        fuel_limit: 1000,
+        nonce: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        network: String::from("SKYNET"),
+        contract: None,
+        method: String::from("transfer"),
+        caller: PublicKey::Ecdsa(EcdsaPublicKey {
+            curve_id: CurveId::Secp384R1,
+            value: vec![4; 97],
+        }),
+        args: vec![0xc0],
+    };
+    Transaction::UnitTransaction(SignedTransaction {
+        data: TransactionData::V1(data),
+        signature: vec![0; 96],
+    })
+}
+
+/// Load the json array of replies
+pub fn load_scenario(path: &str) -> Result<Vec<Reply>> {
+    serde_json::from_slice(&std::fs::read(path)?).map_err(|e| e.into())
+}
+
+/// Serve in the foreground until killed
+pub fn serve(listen: &str, scenario: Vec<Reply>) -> Result<()> {
+    let server = Server::http(listen)?;
+    let state = Mutex::new(NodeState {
+        scenario: scenario.into(),
+        ..Default::default()
+    });
+    eprintln!("Mock node listening on {}", listen);
+    run(&server, &state);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_channel::HttpChannel;
+
+    fn put_request() -> Vec<u8> {
+        let message = Message::PutTransactionRequest {
+            confirm: true,
+            tx: dummy_transaction(),
+        };
+        rmp_serialize(&message).unwrap()
+    }
+
+    #[test]
+    fn scenario_replies() {
+        let node = MockNode::start(vec![
+            Reply::True,
+            Reply::False,
+            Reply::HttpError {
+                status: 503,
+                body: String::from("busy"),
+                accepted: false,
+            },
+        ])
+        .unwrap();
+        let mut channel = HttpChannel::new(node.url());
+
+        channel.send(put_request()).unwrap();
+        assert_eq!(channel.recv().unwrap(), b"true");
+        channel.send(put_request()).unwrap();
+        assert_eq!(channel.recv().unwrap(), b"false");
+        channel.send(put_request()).unwrap();
+        assert_eq!(channel.recv().unwrap_err().to_string(), "busy");
+        assert_eq!(node.received(), 3);
+    }
+
+    #[test]
+    fn accepted_tx_receipt() {
+        let node = MockNode::start(vec![]).unwrap();
+        let mut channel = HttpChannel::new(node.url());
+
+        channel.send(put_request()).unwrap();
+        let hash = match rmp_deserialize::<Message>(&channel.recv().unwrap()).unwrap() {
+            Message::PutTransactionResponse { hash } => hash,
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        let request = Message::GetReceiptRequest { hash };
+        channel.send(rmp_serialize(&request).unwrap()).unwrap();
+
+        match rmp_deserialize::<Message>(&channel.recv().unwrap()).unwrap() {
+            Message::GetReceiptResponse { rx } => assert!(rx.success),
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn http_error_after_accept() {
+        let node = MockNode::start(vec![Reply::HttpError {
+            status: 504,
+            body: String::from("timeout"),
+            accepted: true,
+        }])
+        .unwrap();
+        let mut channel = HttpChannel::new(node.url());
+        let hash = match dummy_transaction() {
+            Transaction::UnitTransaction(sign_tx) => sign_tx.data.primary_hash(),
+            Transaction::BulkTransaction(bulk_tx) => bulk_tx.data.primary_hash(),
+        };
+
+        channel.send(put_request()).unwrap();
+        assert_eq!(channel.recv().unwrap_err().to_string(), "timeout");
+        let request = Message::GetTransactionRequest { hash };
+        channel.send(rmp_serialize(&request).unwrap()).unwrap();
+
+        match rmp_deserialize::<Message>(&channel.recv().unwrap()).unwrap() {
+            Message::GetTransactionResponse { .. } => (),
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn scenario_from_json() {
+        let scenario = r#"[{"reply":"accept","success":false},{"reply":"exception","kind":"invalid_signature"}]"#;
+
+        let res: Vec<Reply> = serde_json::from_str(scenario).unwrap();
+
+        assert_eq!(
+            res,
+            vec![
+                Reply::Accept {
+                    success: false,
+                    returns: String::new()
+                },
+                Reply::Exception {
+                    kind: ExceptionKind::InvalidSignature
+                },
+            ]
+        );
+    }
+}
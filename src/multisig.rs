@@ -0,0 +1,205 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Multi-signature proposals.
+//!
+//! A proposal file holds the approval transaction to be signed by every
+//! required signer. Each signer adds its signed approval offline, then
+//! the collected approvals are submitted in the signers order.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decode::msgpack_to_json,
+    encode::encode_args,
+    secret::PrivateKey,
+    types::{Result, UnitTxArgs},
+};
+
+pub enum MultisigCommand {
+    Propose {
+        tx: TxTemplate,
+        signers: Vec<String>,
+        proposal: String,
+    },
+    Approve {
+        proposal: String,
+        private_key: PrivateKey,
+    },
+    Status {
+        proposal: String,
+    },
+    Submit {
+        proposal: String,
+    },
+}
+
+/// Unit tx args without the private key
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TxTemplate {
+    pub target: String,
+    pub network: String,
+    pub fuel: u64,
+    pub contract: String,
+    pub method: String,
+    pub args: serde_value::Value,
+}
+
+impl TxTemplate {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        serde_json::from_slice(buf).map_err(|e| e.into())
+    }
+
+    pub fn to_unit_tx_args(&self, private_key: PrivateKey) -> UnitTxArgs {
+        UnitTxArgs {
+            target: self.target.clone(),
+            network: self.network.clone(),
+            fuel: self.fuel,
+            contract: self.contract.clone(),
+            method: self.method.clone(),
+            args: self.args.clone(),
+            private_key,
+        }
+    }
+}
+
+/// Signed approval of a signer
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Approval {
+    /// Signer account id
+    pub signer: String,
+    /// Transaction hash HEX
+    pub hash: String,
+    /// Transaction message HEX, as created by `create_unit_tx`
+    pub tx: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Proposal {
+    pub id: String,
+    pub tx: TxTemplate,
+    /// Required signers account ids, in submission order
+    pub signers: Vec<String>,
+    pub approvals: Vec<Approval>,
+}
+
+impl Proposal {
+    pub fn new(mut tx: TxTemplate, signers: Vec<String>) -> Result<Self> {
+        if signers.is_empty() {
+            return Err("at least one signer is required".into());
+        }
+        // Binary args are stored with the typed hints, so they are encoded
+        // the same way by every signer
+        let (args, _) = msgpack_to_json(&encode_args(&tx.args)?)?;
+        tx.args = serde_value::to_value(args)?;
+
+        Ok(Proposal {
+            id: hex::encode(rand::random::<[u8; 16]>()),
+            tx,
+            signers,
+            approvals: vec![],
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        serde_json::from_slice(&std::fs::read(path)?).map_err(|e| e.into())
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?).map_err(|e| e.into())
+    }
+
+    pub fn add_approval(&mut self, approval: Approval) -> Result<()> {
+        if !self.signers.contains(&approval.signer) {
+            return Err(format!("`{}` is not a signer of the proposal", approval.signer).into());
+        }
+        if self.approval(&approval.signer).is_some() {
+            return Err(format!("`{}` has already approved the proposal", approval.signer).into());
+        }
+        self.approvals.push(approval);
+        Ok(())
+    }
+
+    pub fn approval(&self, signer: &str) -> Option<&Approval> {
+        self.approvals
+            .iter()
+            .find(|approval| approval.signer == signer)
+    }
+
+    /// Signers that have not approved yet
+    pub fn missing(&self) -> Vec<&String> {
+        self.signers
+            .iter()
+            .filter(|signer| self.approval(signer).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_proposal() -> Proposal {
+        let tx = TxTemplate {
+            target: String::from("#TREASURY"),
+            network: String::from("SKYNET"),
+            fuel: 1000,
+            contract: String::new(),
+            method: String::from("approve"),
+            args: serde_value::Value::Bytes(vec![1, 2]),
+        };
+        Proposal::new(tx, vec![String::from("SIGNER1"), String::from("SIGNER2")]).unwrap()
+    }
+
+    fn create_approval(signer: &str) -> Approval {
+        Approval {
+            signer: signer.to_string(),
+            hash: String::from("1220"),
+            tx: String::from("00"),
+        }
+    }
+
+    #[test]
+    fn proposal_args_with_hints() {
+        let proposal = create_proposal();
+
+        let res = encode_args(&proposal.tx.args).unwrap();
+
+        assert_eq!(res, [196, 2, 1, 2]);
+    }
+
+    #[test]
+    fn proposal_approvals() {
+        let mut proposal = create_proposal();
+
+        proposal.add_approval(create_approval("SIGNER2")).unwrap();
+
+        assert_eq!(proposal.missing(), vec!["SIGNER1"]);
+        assert!(proposal.add_approval(create_approval("SIGNER2")).is_err());
+        assert!(proposal.add_approval(create_approval("SIGNER3")).is_err());
+    }
+
+    #[test]
+    fn tx_template_ignores_private_key() {
+        let json = r##"{"target":"#TREASURY","network":"SKYNET","fuel":1000,"contract":"","method":"approve","args":{},"private_key":"secret"}"##;
+
+        let res = TxTemplate::from_json_slice(json.as_bytes()).unwrap();
+
+        assert_eq!(res.method, "approve");
+        assert!(!serde_json::to_string(&res).unwrap().contains("secret"));
+    }
+}
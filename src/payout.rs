@@ -0,0 +1,357 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bulk payouts from a CSV of recipients.
+//!
+//! The input CSV has the `account`, `units` and optional `memo` columns, the
+//! result CSV adds the transaction `hash` and the `outcome` of every row:
+//! ```text
+//! account,units,memo,hash,outcome
+//! #ALICE,100,May,1220...,success
+//! #BOB,50,,1220...,failed: insufficient funds
+//! #CAROL,70,,1220...,KO|DuplicatedUnconfirmedTx
+//! #DAVE,10,,1220...,pending
+//! #ERIN,5,,1220...,unknown: connection reset
+//! ```
+//! The result is a valid input: the `success` and `pending` rows are skipped,
+//! the `unknown` ones are looked up on the node and the other ones paid again.
+
+use std::time::Duration;
+
+use serde_value::Value;
+
+use crate::{
+    asset::AssetOperation,
+    secret::PrivateKey,
+    template::{rows_from_csv, Template, Vars},
+    types::{Result, UnitTxArgs},
+    validation::check_account_id,
+};
+
+/// Outcome of an executed transaction
+pub const OUTCOME_SUCCESS: &str = "success";
+/// Outcome of an accepted transaction without a receipt yet
+pub const OUTCOME_PENDING: &str = "pending";
+/// Outcome prefix of an accepted transaction whose receipt request failed
+pub const OUTCOME_RECEIPT_ERROR: &str = "failed: receipt: ";
+/// Outcome prefix of a submission without an answer, the node may have
+/// accepted the transaction
+pub const OUTCOME_UNKNOWN: &str = "unknown: ";
+
+const HEADERS: [&str; 5] = ["account", "units", "memo", "hash", "outcome"];
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PayoutRow {
+    pub account: String,
+    pub units: u64,
+    pub memo: String,
+    /// Transaction hash HEX, empty if not submitted
+    pub hash: String,
+    /// `success`, `pending`, `failed: <ERROR>`, `failed: receipt: <ERROR>`,
+    /// `unknown: <ERROR>` or the rejected submission `KO|...`
+    pub outcome: String,
+}
+
+impl PayoutRow {
+    fn from_vars(vars: &Vars) -> std::result::Result<Self, String> {
+        let column = |name: &str| vars.get(name).cloned().unwrap_or_default();
+
+        let account = column("account");
+        check_account_id(&account).map_err(|e| format!("account: {}", e))?;
+        let units = column("units")
+            .parse()
+            .map_err(|e| format!("units: {}", e))?;
+        Ok(PayoutRow {
+            account,
+            units,
+            memo: column("memo"),
+            hash: column("hash"),
+            outcome: column("outcome"),
+        })
+    }
+
+    /// Paid, or possibly paid, rows are not paid again
+    pub fn is_settled(&self) -> bool {
+        self.outcome == OUTCOME_SUCCESS || self.is_awaiting_receipt()
+    }
+
+    /// Accepted, or possibly accepted, rows whose receipt is still unknown
+    pub fn is_awaiting_receipt(&self) -> bool {
+        self.outcome == OUTCOME_PENDING
+            || self.outcome.starts_with(OUTCOME_RECEIPT_ERROR)
+            || self.is_unknown()
+    }
+
+    /// Submitted rows without an answer from the node
+    pub fn is_unknown(&self) -> bool {
+        self.outcome.starts_with(OUTCOME_UNKNOWN)
+    }
+
+    /// Values of the template placeholders
+    fn vars(&self) -> Vars {
+        [
+            ("account", self.account.clone()),
+            ("units", self.units.to_string()),
+            ("memo", self.memo.clone()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+}
+
+/// Transaction paying a row
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayoutTx {
+    /// Standard asset transfer, the `memo` is added to the args if not empty
+    Transfer {
+        asset: String,
+        network: String,
+        fuel: u64,
+        contract: String,
+        from: String,
+    },
+    /// Template with the `account`, `units` and `memo` placeholders
+    Template(Template),
+}
+
+impl PayoutTx {
+    pub fn unit_tx_args(&self, row: &PayoutRow, private_key: PrivateKey) -> Result<UnitTxArgs> {
+        match self {
+            PayoutTx::Transfer {
+                asset,
+                network,
+                fuel,
+                contract,
+                from,
+            } => {
+                let operation = AssetOperation::Transfer {
+                    from: from.clone(),
+                    to: row.account.clone(),
+                    units: row.units,
+                };
+                let mut args = operation.args();
+                if let (Value::Map(map), false) = (&mut args, row.memo.is_empty()) {
+                    map.insert(
+                        Value::String(String::from("memo")),
+                        Value::String(row.memo.clone()),
+                    );
+                }
+                Ok(UnitTxArgs {
+                    target: asset.clone(),
+                    network: network.clone(),
+                    fuel: *fuel,
+                    contract: contract.clone(),
+                    method: operation.method().to_string(),
+                    args,
+                    private_key,
+                })
+            }
+            PayoutTx::Template(template) => {
+                // Only the columns used by the template are given
+                let placeholders = template.placeholders()?;
+                let vars = row
+                    .vars()
+                    .into_iter()
+                    .filter(|(name, _)| placeholders.contains_key(name))
+                    .collect();
+                template.fill(&vars, private_key)
+            }
+        }
+    }
+}
+
+pub struct PayoutConfig {
+    /// Input CSV
+    pub rows: String,
+    /// Result CSV, may be the input one
+    pub result: String,
+    pub tx: PayoutTx,
+    pub private_key: PrivateKey,
+    /// Rows packed in a bulk transaction, 1 for unit transactions
+    pub bulk_size: usize,
+    /// Minimum delay between two submissions
+    pub interval: Duration,
+}
+
+/// Rows of the CSV, all the invalid rows are reported together
+pub fn rows_from_slice(buf: &[u8]) -> Result<Vec<PayoutRow>> {
+    let mut rows = vec![];
+    let mut errors = vec![];
+    for (i, vars) in rows_from_csv(buf)?.iter().enumerate() {
+        match PayoutRow::from_vars(vars) {
+            Ok(row) => rows.push(row),
+            // The header is the line 1
+            Err(e) => errors.push(format!("line {}: {}", i + 2, e)),
+        }
+    }
+    if errors.is_empty() {
+        Ok(rows)
+    } else {
+        Err(errors.join("; ").into())
+    }
+}
+
+pub fn load(path: &str) -> Result<Vec<PayoutRow>> {
+    rows_from_slice(&std::fs::read(path)?)
+}
+
+pub fn rows_to_csv(rows: &[PayoutRow]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(HEADERS)?;
+    for row in rows {
+        let units = row.units.to_string();
+        writer.write_record([
+            row.account.as_str(),
+            units.as_str(),
+            row.memo.as_str(),
+            row.hash.as_str(),
+            row.outcome.as_str(),
+        ])?;
+    }
+    writer.into_inner().map_err(|e| e.to_string().into())
+}
+
+/// Write the result, replacing the file at once
+pub fn save(path: &str, rows: &[PayoutRow]) -> Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    std::fs::write(&tmp_path, rows_to_csv(rows)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Count of the rows by outcome
+pub fn summary(rows: &[PayoutRow]) -> String {
+    let count = |f: &dyn Fn(&PayoutRow) -> bool| rows.iter().filter(|row| f(row)).count();
+    format!(
+        "{} rows: {} success, {} pending, {} failed, {} rejected, {} unknown",
+        rows.len(),
+        count(&|row| row.outcome == OUTCOME_SUCCESS),
+        count(&|row| row.outcome == OUTCOME_PENDING),
+        count(&|row| row.outcome.starts_with("failed")),
+        count(&|row| row.outcome.starts_with("KO|")),
+        count(&PayoutRow::is_unknown),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::{encode_args, encode_json};
+    use serde_json::json;
+
+    const RESULT_CSV: &str = "account,units,memo,hash,outcome
+#ALICE,100,May,1220ab,success
+#BOB,50,,1220cd,failed: insufficient funds
+#CAROL,70,,,KO|DuplicatedUnconfirmedTx
+#DAVE,10,,1220ef,pending
+#ERIN,5,,1220aa,failed: receipt: connection refused
+#FRANK,20,,1220bb,unknown: connection reset
+";
+
+    #[test]
+    fn result_round_trip() {
+        let rows = rows_from_slice(RESULT_CSV.as_bytes()).unwrap();
+
+        let res = rows_to_csv(&rows).unwrap();
+
+        assert_eq!(String::from_utf8(res).unwrap(), RESULT_CSV);
+        let settled: Vec<bool> = rows.iter().map(PayoutRow::is_settled).collect();
+        assert_eq!(settled, [true, false, false, true, true, true]);
+        assert_eq!(
+            summary(&rows),
+            "6 rows: 1 success, 1 pending, 2 failed, 1 rejected, 1 unknown"
+        );
+    }
+
+    #[test]
+    fn input_without_result_columns() {
+        let res = rows_from_slice(b"account,units\n#ALICE,100\n").unwrap();
+
+        assert_eq!(
+            res,
+            vec![PayoutRow {
+                account: String::from("#ALICE"),
+                units: 100,
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_rows() {
+        let err = rows_from_slice(b"account,units\n#ALICE,ten\n,5\n#BOB,1\n")
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            err,
+            "line 2: units: invalid digit found in string; line 3: account: empty account id"
+        );
+    }
+
+    #[test]
+    fn transfer_args_with_memo() {
+        let tx = PayoutTx::Transfer {
+            asset: String::from("#MYASSET"),
+            network: String::from("SKYNET"),
+            fuel: 1000,
+            contract: String::new(),
+            from: String::from("#TREASURY"),
+        };
+        let row = PayoutRow {
+            account: String::from("#ALICE"),
+            units: 100,
+            memo: String::from("May"),
+            ..Default::default()
+        };
+
+        let res = tx.unit_tx_args(&row, PrivateKey::default()).unwrap();
+
+        assert_eq!(res.target, "#MYASSET");
+        assert_eq!(res.method, "transfer");
+        let expected = json!({"from": "#TREASURY", "to": "#ALICE", "units": 100, "memo": "May"});
+        assert_eq!(
+            encode_args(&res.args).unwrap(),
+            encode_json(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn template_args() {
+        let template = Template::from_json_slice(
+            br##"{"target":"#MYASSET","network":"SKYNET","fuel":1000,"contract":"","method":"pay","args":{"to":"{{account}}","units":"{{units:u64}}"}}"##,
+        )
+        .unwrap();
+        let row = PayoutRow {
+            account: String::from("#ALICE"),
+            units: 100,
+            memo: String::from("unused"),
+            ..Default::default()
+        };
+
+        let res = PayoutTx::Template(template)
+            .unit_tx_args(&row, PrivateKey::default())
+            .unwrap();
+
+        assert_eq!(res.method, "pay");
+        assert_eq!(
+            encode_args(&res.args).unwrap(),
+            encode_json(json!({"to": "#ALICE", "units": 100})).unwrap()
+        );
+    }
+}
@@ -0,0 +1,204 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! PKCS#11 signer, the private key never leaves the token.
+//!
+//! The token must hold a secp384r1 key pair, the private and the public key
+//! objects with the same label.
+
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+};
+use sha2::{Digest, Sha384};
+use trinci_core::{
+    crypto::ecdsa::{CurveId, PublicKey as EcdsaPublicKey},
+    PublicKey,
+};
+use zeroize::Zeroizing;
+
+use crate::{signer::Signer, types::Result};
+
+/// Length of an uncompressed secp384r1 point
+const EC_POINT_LEN: usize = 97;
+
+pub struct Pkcs11Signer {
+    session: Session,
+    private_key: ObjectHandle,
+    public_key: ObjectHandle,
+    // Declared after the session, that must be closed first
+    _pkcs11: Pkcs11,
+}
+
+fn find_key(session: &Session, class: ObjectClass, label: &str) -> Result<ObjectHandle> {
+    let template = [
+        Attribute::Class(class),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ];
+    match session.find_objects(&template)?.as_slice() {
+        [handle] => Ok(*handle),
+        [] => Err(format!("no {:?} with label `{}` in the token", class, label).into()),
+        _ => Err(format!(
+            "more than one {:?} with label `{}` in the token",
+            class, label
+        )
+        .into()),
+    }
+}
+
+/// Extract the point from the DER octet string of the `CKA_EC_POINT` attribute.
+/// Some modules return the raw point.
+fn ec_point_from_der(der: &[u8]) -> Result<Vec<u8>> {
+    match der {
+        [0x04, len, point @ ..] if *len as usize == EC_POINT_LEN && point.len() == EC_POINT_LEN => {
+            Ok(point.to_vec())
+        }
+        point if point.len() == EC_POINT_LEN && point[0] == 0x04 => Ok(point.to_vec()),
+        _ => Err("unexpected EC point, a secp384r1 key is required".into()),
+    }
+}
+
+impl Pkcs11Signer {
+    /// Open a session on the token, logged in with the user PIN
+    pub fn open(
+        module: &str,
+        token: Option<&str>,
+        key: &str,
+        pin: Zeroizing<String>,
+    ) -> Result<Self> {
+        let pkcs11 = Pkcs11::new(module)?;
+        pkcs11.initialize(CInitializeArgs::OsThreads)?;
+
+        let mut slot = None;
+        for candidate in pkcs11.get_slots_with_token()? {
+            let label = pkcs11.get_token_info(candidate)?.label().trim().to_string();
+            if token.map_or(true, |token| token == label) {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| match token {
+            Some(token) => format!("PKCS#11 token `{}` not found", token),
+            None => "no PKCS#11 token found".to_string(),
+        })?;
+
+        let session = pkcs11.open_ro_session(slot)?;
+        session.login(UserType::User, Some(pin.as_str()))?;
+        drop(pin);
+
+        let private_key = find_key(&session, ObjectClass::PRIVATE_KEY, key)?;
+        let public_key = find_key(&session, ObjectClass::PUBLIC_KEY, key)?;
+
+        Ok(Pkcs11Signer {
+            session,
+            private_key,
+            public_key,
+            _pkcs11: pkcs11,
+        })
+    }
+}
+
+impl Signer for Pkcs11Signer {
+    fn public_key(&self) -> Result<PublicKey> {
+        let attributes = self
+            .session
+            .get_attributes(self.public_key, &[AttributeType::EcPoint])?;
+        let point = match attributes.as_slice() {
+            [Attribute::EcPoint(der)] => ec_point_from_der(der)?,
+            _ => return Err("public key without EC point".into()),
+        };
+        Ok(PublicKey::Ecdsa(EcdsaPublicKey {
+            curve_id: CurveId::Secp384R1,
+            value: point,
+        }))
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        // `CKM_ECDSA` signs the digest, the signature is the fixed `r || s`
+        // as the one of the local signer
+        let digest = Sha384::digest(data);
+        self.session
+            .sign(&Mechanism::Ecdsa, self.private_key, &digest)
+            .map_err(|e| e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::PKCS11_PIN_ENV;
+    use trinci_core::{
+        base::{schema::TransactionData, serialize::MessagePack},
+        TransactionDataV1,
+    };
+
+    /// Signs with a real token, eg the SoftHSM one of the README:
+    /// `TRINCI_PKCS11_TEST_MODULE=/usr/lib/softhsm/libsofthsm2.so TRINCI_PKCS11_TEST_TOKEN=trinci
+    /// TRINCI_PKCS11_TEST_KEY=treasury TRINCI_PKCS11_PIN=1234 cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn sign_with_token() {
+        let module = match std::env::var("TRINCI_PKCS11_TEST_MODULE") {
+            Ok(module) => module,
+            Err(_) => {
+                eprintln!("TRINCI_PKCS11_TEST_MODULE not set, skipped");
+                return;
+            }
+        };
+        let token = std::env::var("TRINCI_PKCS11_TEST_TOKEN").ok();
+        let key = std::env::var("TRINCI_PKCS11_TEST_KEY").unwrap_or_else(|_| "treasury".into());
+        let pin = Zeroizing::new(std::env::var(PKCS11_PIN_ENV).unwrap());
+        let signer = Pkcs11Signer::open(&module, token.as_deref(), &key, pin).unwrap();
+        let caller = signer.public_key().unwrap();
+        let data = TransactionData::V1(TransactionDataV1 {
+            account: String::from("#MYASSET"),
+            fuel_limit: 1000,
+            nonce: vec![0; 8],
+            network: String::from("SKYNET"),
+            contract: None,
+            method: String::from("transfer"),
+            caller: caller.clone(),
+            args: vec![0xc0],
+        });
+        let buf = data.serialize();
+
+        let signature = signer.sign(&buf).unwrap();
+
+        assert!(caller.verify(&buf, &signature));
+    }
+
+    #[test]
+    fn ec_point_der_octet_string() {
+        let mut der = vec![0x04, EC_POINT_LEN as u8, 0x04];
+        der.extend_from_slice(&[1; EC_POINT_LEN - 1]);
+
+        let res = ec_point_from_der(&der).unwrap();
+
+        assert_eq!(res, der[2..]);
+    }
+
+    #[test]
+    fn ec_point_wrong_curve() {
+        // Uncompressed secp256r1 point
+        let mut point = vec![0x04];
+        point.extend_from_slice(&[1; 64]);
+
+        assert!(ec_point_from_der(&point).is_err());
+    }
+}
@@ -0,0 +1,358 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Signing policies.
+//!
+//! A policy file restricts what a key may sign, every field is optional
+//! and an omitted field does not restrict anything:
+//! ```json
+//! {
+//!     "signer": "QmSigner...",
+//!     "networks": ["SKYNET"],
+//!     "targets": ["#MYASSET"],
+//!     "contracts": ["12205bdc...", ""],
+//!     "methods": ["transfer"],
+//!     "max_fuel": 1000,
+//!     "args": {
+//!         "units": { "max": 100 },
+//!         "to": { "values": ["#ACCOUNT1", "#ACCOUNT2"] }
+//!     }
+//! }
+//! ```
+//! An empty string in `contracts` allows the account default contract.
+//!
+//! The policy is checked on the transaction data about to be signed, the args
+//! are decoded from their messagepack: they must be a map holding every
+//! constrained field, without repeated keys at any depth (the contract may
+//! read a different value than the checked one).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_value::Value;
+use trinci_core::TransactionDataV1;
+
+use crate::{
+    abi::{self, TypeSchema},
+    decode::{hash_to_hex, msgpack_to_json},
+    encode::encode_args,
+    types::Result,
+};
+
+/// Constraint on a contract argument
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ArgConstraint {
+    /// Minimum integer value
+    pub min: Option<u64>,
+    /// Maximum integer value
+    pub max: Option<u64>,
+    /// Allowed values, in json with the typed hints
+    pub values: Option<Vec<serde_json::Value>>,
+}
+
+/// Restrictions on the transactions a signer may sign
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SigningPolicy {
+    /// Account id of the key the policy is bound to
+    pub signer: Option<String>,
+    pub networks: Option<Vec<String>>,
+    pub targets: Option<Vec<String>>,
+    /// Contracts multihash HEX
+    pub contracts: Option<Vec<String>>,
+    pub methods: Option<Vec<String>>,
+    pub max_fuel: Option<u64>,
+    #[serde(default)]
+    pub args: BTreeMap<String, ArgConstraint>,
+}
+
+fn check_allowed(
+    field: &str,
+    value: &str,
+    allowed: &Option<Vec<String>>,
+    errors: &mut Vec<String>,
+) {
+    if let Some(allowed) = allowed {
+        if !allowed.iter().any(|item| item == value) {
+            errors.push(format!("{}: `{}` is not allowed", field, value));
+        }
+    }
+}
+
+fn check_arg(name: &str, value: &Value, constraint: &ArgConstraint, errors: &mut Vec<String>) {
+    let path = format!("args.{}", name);
+
+    if constraint.min.is_some() || constraint.max.is_some() {
+        match abi::coerce(value, &TypeSchema::U64, &path) {
+            Ok(Value::U64(val)) => {
+                let min = constraint.min.unwrap_or(u64::MIN);
+                let max = constraint.max.unwrap_or(u64::MAX);
+                if val < min || val > max {
+                    errors.push(format!(
+                        "{}: {} is out of bounds [{}, {}]",
+                        path, val, min, max
+                    ));
+                }
+            }
+            _ => errors.push(format!("{}: not an unsigned integer", path)),
+        }
+    }
+
+    if let Some(values) = &constraint.values {
+        let json = encode_args(value)
+            .and_then(|buf| msgpack_to_json(&buf))
+            .map(|(json, _)| json);
+        match json {
+            Ok(json) if values.contains(&json) => (),
+            Ok(json) => errors.push(format!("{}: {} is not allowed", path, json)),
+            Err(e) => errors.push(format!("{}: {}", path, e)),
+        }
+    }
+}
+
+/// First key repeated in a map of the value, the nested ones included
+fn duplicate_key(value: &rmpv::Value) -> Option<String> {
+    match value {
+        rmpv::Value::Map(entries) => entries.iter().enumerate().find_map(|(i, (key, item))| {
+            if entries[..i].iter().any(|(other, _)| other == key) {
+                Some(key.as_str().map_or_else(|| key.to_string(), str::to_string))
+            } else {
+                duplicate_key(item)
+            }
+        }),
+        rmpv::Value::Array(items) => items.iter().find_map(duplicate_key),
+        _ => None,
+    }
+}
+
+impl SigningPolicy {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        serde_json::from_slice(buf).map_err(|e| e.into())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+
+    /// Check the transaction data the caller is about to sign,
+    /// collecting a denial reason for every violated restriction
+    pub fn check(&self, data: &TransactionDataV1) -> Result<()> {
+        let mut errors = vec![];
+
+        if let Some(policy_signer) = &self.signer {
+            if *policy_signer != data.caller.to_account_id() {
+                errors.push(format!(
+                    "signer: the policy is bound to `{}`",
+                    policy_signer
+                ));
+            }
+        }
+        check_allowed("network", &data.network, &self.networks, &mut errors);
+        check_allowed("target", &data.account, &self.targets, &mut errors);
+        // Multihash HEX is case insensitive
+        check_allowed(
+            "contract",
+            &data.contract.as_ref().map(hash_to_hex).unwrap_or_default(),
+            &self
+                .contracts
+                .as_ref()
+                .map(|contracts| contracts.iter().map(|c| c.to_lowercase()).collect()),
+            &mut errors,
+        );
+        check_allowed("method", &data.method, &self.methods, &mut errors);
+        if let Some(max_fuel) = self.max_fuel {
+            if data.fuel_limit > max_fuel {
+                errors.push(format!(
+                    "fuel: {} exceeds the maximum {}",
+                    data.fuel_limit, max_fuel
+                ));
+            }
+        }
+
+        if !self.args.is_empty() {
+            let duplicate = rmpv::decode::read_value(&mut data.args.as_slice())
+                .ok()
+                .and_then(|value| duplicate_key(&value));
+            match (duplicate, rmp_serde::from_slice::<Value>(&data.args)) {
+                (Some(key), _) => errors.push(format!("args: duplicate key `{}`", key)),
+                (None, Ok(Value::Map(map))) => {
+                    for (name, constraint) in &self.args {
+                        match map.get(&Value::String(name.to_string())) {
+                            Some(value) => check_arg(name, value, constraint, &mut errors),
+                            None => errors.push(format!("args.{}: missing", name)),
+                        }
+                    }
+                }
+                _ => errors.push("args: constrained args must be a map".to_string()),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("denied by the signing policy: {}", errors.join("; ")).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trinci_core::{
+        crypto::ecdsa::{CurveId, PublicKey as EcdsaPublicKey},
+        PublicKey,
+    };
+
+    fn caller() -> PublicKey {
+        PublicKey::Ecdsa(EcdsaPublicKey {
+            curve_id: CurveId::Secp384R1,
+            value: vec![4; 97],
+        })
+    }
+
+    fn create_policy() -> SigningPolicy {
+        let policy = format!(
+            r##"{{
+                "signer": "{}",
+                "networks": ["SKYNET"],
+                "targets": ["#MYASSET"],
+                "contracts": [""],
+                "methods": ["transfer"],
+                "max_fuel": 1000,
+                "args": {{
+                    "units": {{ "max": 100 }},
+                    "to": {{ "values": ["#ACCOUNT1"] }}
+                }}
+            }}"##,
+            caller().to_account_id()
+        );
+        SigningPolicy::from_json_slice(policy.as_bytes()).unwrap()
+    }
+
+    /// Transaction data with the json args encoded with the typed hints
+    fn create_tx_data(args: &str) -> TransactionDataV1 {
+        let args: Value = serde_json::from_str(args).unwrap();
+        TransactionDataV1 {
+            account: String::from("#MYASSET"),
+            fuel_limit: 1000,
+            nonce: vec![0; 8],
+            network: String::from("SKYNET"),
+            contract: None,
+            method: String::from("transfer"),
+            caller: caller(),
+            args: encode_args(&args).unwrap(),
+        }
+    }
+
+    #[test]
+    fn policy_allows() {
+        let policy = create_policy();
+        let data = create_tx_data(r##"{"to":"#ACCOUNT1","units":{"$u64":"100"}}"##);
+
+        assert!(policy.check(&data).is_ok());
+    }
+
+    #[test]
+    fn policy_denies_with_reasons() {
+        let policy = create_policy();
+        let mut data = create_tx_data(r##"{"to":"#ACCOUNT2","units":101}"##);
+        data.method = String::from("mint");
+        data.fuel_limit = 1001;
+        data.caller = PublicKey::Ecdsa(EcdsaPublicKey {
+            curve_id: CurveId::Secp384R1,
+            value: vec![5; 97],
+        });
+
+        let err = policy.check(&data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "denied by the signing policy: signer: the policy is bound to `{}`; \
+                 method: `mint` is not allowed; fuel: 1001 exceeds the maximum 1000; \
+                 args.to: \"#ACCOUNT2\" is not allowed; args.units: 101 is out of bounds [0, 100]",
+                caller().to_account_id()
+            )
+        );
+    }
+
+    #[test]
+    fn policy_checks_the_encoded_args() {
+        let policy = create_policy();
+        // A map hint is a one key map before the encoding
+        let data = create_tx_data(r##"{"$map": [["units", 1000000000], ["to", "#EVIL"]]}"##);
+
+        let err = policy.check(&data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "denied by the signing policy: args.to: \"#EVIL\" is not allowed; \
+             args.units: 1000000000 is out of bounds [0, 100]"
+        );
+    }
+
+    #[test]
+    fn policy_denies_duplicate_keys() {
+        let policy = create_policy();
+        let data =
+            create_tx_data(r##"{"$map":[["units",1000000],["units",1],["to","#ACCOUNT1"]]}"##);
+
+        let err = policy.check(&data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "denied by the signing policy: args: duplicate key `units`"
+        );
+    }
+
+    #[test]
+    fn policy_denies_missing_args() {
+        let policy = create_policy();
+        let data = create_tx_data(r##"{"to":"#ACCOUNT1"}"##);
+
+        let err = policy.check(&data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "denied by the signing policy: args.units: missing"
+        );
+    }
+
+    #[test]
+    fn policy_checks_the_signed_contract() {
+        let mut policy = create_policy();
+        policy.contracts = Some(vec![String::from(
+            "12205BDCA17463A5FBB92D461B61EC5B502AB2645C3487C94862F9B18C37BC01C118",
+        )]);
+        let data = create_tx_data(r##"{"to":"#ACCOUNT1","units":1}"##);
+
+        let err = policy.check(&data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "denied by the signing policy: contract: `` is not allowed"
+        );
+    }
+
+    #[test]
+    fn policy_unknown_field() {
+        let policy = r#"{"max_fule": 1000}"#;
+
+        assert!(SigningPolicy::from_json_slice(policy.as_bytes()).is_err());
+    }
+}
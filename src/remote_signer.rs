@@ -0,0 +1,395 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Remote signer protocol.
+//!
+//! The transaction builder and the host holding the keys talk json over HTTP,
+//! the keys are selected by alias:
+//!  - `POST /public_key` `{"alias"}` -> `{"public_key"}`
+//!  - `POST /sign` `{"alias", "data"}` -> `{"signature", "public_key"}`
+//!
+//! `data` is the serialized `TransactionData` HEX, `signature` is HEX and
+//! `public_key` is the messagepacked public key HEX. Errors are answered
+//! with a non 200 status and `{"error"}`. When the server has a token the
+//! requests must carry the `Authorization: Bearer <token>` header.
+
+use std::{collections::BTreeMap, io::Read};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tiny_http::{Header, Request, Response, Server};
+use trinci_core::{
+    base::{
+        schema::{SignedTransaction, TransactionData},
+        serialize::{rmp_deserialize, rmp_serialize, MessagePack},
+    },
+    PublicKey,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    audit,
+    policy::SigningPolicy,
+    secret::PrivateKey,
+    signer::{Signer, SignerConfig},
+    types::Result,
+};
+
+/// Default environment variable holding the remote signer token
+pub const REMOTE_SIGNER_TOKEN_ENV: &str = "TRINCI_REMOTE_SIGNER_TOKEN";
+
+/// Maximum request body accepted by the server
+const MAX_BODY_LEN: u64 = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicKeyRequest {
+    pub alias: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicKeyResponse {
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignRequest {
+    pub alias: String,
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignResponse {
+    pub signature: String,
+    pub public_key: String,
+}
+
+fn public_key_to_hex(public_key: &PublicKey) -> Result<String> {
+    Ok(hex::encode(rmp_serialize(public_key)?))
+}
+
+fn public_key_from_hex(text: &str) -> Result<PublicKey> {
+    rmp_deserialize(&hex::decode(text)?).map_err(|e| e.into())
+}
+
+/// Signer delegating the signature to a remote signer server
+pub struct RemoteSigner {
+    url: String,
+    alias: String,
+    token: Option<Zeroizing<String>>,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, alias: &str, token: Option<Zeroizing<String>>) -> Self {
+        RemoteSigner {
+            url: url.trim_end_matches('/').to_string(),
+            alias: alias.to_string(),
+            token,
+        }
+    }
+
+    fn post<T: Serialize, R: DeserializeOwned>(&self, path: &str, req: &T) -> Result<R> {
+        let mut request = ureq::post(&format!("{}{}", self.url, path));
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token.as_str()));
+        }
+        match request.send_json(serde_json::to_value(req)?) {
+            Ok(res) => res.into_json::<R>().map_err(|e| e.into()),
+            Err(ureq::Error::Status(code, res)) => {
+                let error = res
+                    .into_json::<serde_json::Value>()
+                    .ok()
+                    .and_then(|value| value["error"].as_str().map(str::to_string))
+                    .unwrap_or_default();
+                Err(format!("remote signer error {}: {}", code, error).into())
+            }
+            Err(err) => Err(format!("remote signer error {}", err).into()),
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> Result<PublicKey> {
+        let res: PublicKeyResponse = self.post(
+            "/public_key",
+            &PublicKeyRequest {
+                alias: self.alias.clone(),
+            },
+        )?;
+        public_key_from_hex(&res.public_key)
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let res: SignResponse = self.post(
+            "/sign",
+            &SignRequest {
+                alias: self.alias.clone(),
+                data: hex::encode(data),
+            },
+        )?;
+        hex::decode(res.signature).map_err(|e| e.into())
+    }
+}
+
+/// Key held by the server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AliasConfig {
+    /// Signer backend, `local` needs the `private_key_file`
+    pub signer: SignerConfig,
+    /// File with the base58 of the private key bytes array in pkcs8
+    pub private_key_file: Option<String>,
+    /// Policy enforced on the signed transactions
+    pub policy: Option<SigningPolicy>,
+}
+
+/// Remote signer server config
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    pub signers: BTreeMap<String, AliasConfig>,
+    /// Audit log file where the produced signatures are recorded
+    pub audit_log: Option<String>,
+    /// Environment variable holding the token required to the clients
+    pub token_env: Option<String>,
+}
+
+impl ServerConfig {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        serde_json::from_slice(buf).map_err(|e| e.into())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+}
+
+struct AliasSigner {
+    signer: Box<dyn Signer>,
+    public_key: PublicKey,
+    policy: Option<SigningPolicy>,
+}
+
+impl AliasSigner {
+    fn open(config: &AliasConfig) -> Result<Self> {
+        let private_key = match &config.private_key_file {
+            Some(path) => {
+                let buf = Zeroizing::new(std::fs::read(path)?);
+                PrivateKey::new(std::str::from_utf8(&buf)?.trim().to_string())
+            }
+            None => PrivateKey::default(),
+        };
+        let signer = config.signer.create_signer(&private_key)?;
+        let public_key = signer.public_key()?;
+        Ok(AliasSigner {
+            signer,
+            public_key,
+            policy: config.policy.clone(),
+        })
+    }
+
+    /// Check and sign the serialized transaction data
+    fn sign(&self, buf: &[u8]) -> std::result::Result<SignedTransaction, (u16, String)> {
+        let data = rmp_deserialize::<TransactionData>(buf)
+            .map_err(|e| (400, format!("invalid transaction data: {}", e)))?;
+        // The policy is checked on the decoded data, the signed bytes must be the same
+        if data.serialize() != buf {
+            return Err((400, "non canonical transaction data".to_string()));
+        }
+        let data_v1 = match &data {
+            TransactionData::V1(data_v1) => data_v1,
+            _ => return Err((400, "unexpected transaction data".to_string())),
+        };
+        if data_v1.caller.to_account_id() != self.public_key.to_account_id() {
+            return Err((403, "the caller is not the alias public key".to_string()));
+        }
+
+        if let Some(policy) = &self.policy {
+            policy.check(data_v1).map_err(|e| (403, e.to_string()))?;
+        }
+
+        let signature = self.signer.sign(buf).map_err(|e| (500, e.to_string()))?;
+        Ok(SignedTransaction { data, signature })
+    }
+}
+
+/// Compare the `Authorization` header value with the bearer token in constant time
+fn bearer_matches(value: &str, token: &str) -> bool {
+    match value.strip_prefix("Bearer ") {
+        Some(value) => value.as_bytes().ct_eq(token.as_bytes()).into(),
+        None => false,
+    }
+}
+
+struct SignerServer {
+    signers: BTreeMap<String, AliasSigner>,
+    audit_log: Option<String>,
+    token: Option<Zeroizing<String>>,
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> std::result::Result<T, (u16, String)> {
+    serde_json::from_slice(body).map_err(|e| (400, format!("invalid request: {}", e)))
+}
+
+impl SignerServer {
+    fn alias(&self, alias: &str) -> std::result::Result<&AliasSigner, (u16, String)> {
+        self.signers
+            .get(alias)
+            .ok_or_else(|| (404, format!("unknown signer alias `{}`", alias)))
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        match &self.token {
+            Some(token) => request.headers().iter().any(|header| {
+                header.field.equiv("Authorization") && bearer_matches(header.value.as_str(), token)
+            }),
+            None => true,
+        }
+    }
+
+    fn handle(
+        &self,
+        request: &mut Request,
+    ) -> std::result::Result<serde_json::Value, (u16, String)> {
+        if !self.authorized(request) {
+            return Err((401, "unauthorized".to_string()));
+        }
+        let mut body = vec![];
+        request
+            .as_reader()
+            .take(MAX_BODY_LEN)
+            .read_to_end(&mut body)
+            .map_err(|e| (400, e.to_string()))?;
+
+        match request.url() {
+            "/public_key" => {
+                let req: PublicKeyRequest = parse_body(&body)?;
+                let public_key = public_key_to_hex(&self.alias(&req.alias)?.public_key)
+                    .map_err(|e| (500, e.to_string()))?;
+                Ok(json!(PublicKeyResponse { public_key }))
+            }
+            "/sign" => {
+                let req: SignRequest = parse_body(&body)?;
+                let alias_signer = self.alias(&req.alias)?;
+                let buf = hex::decode(&req.data).map_err(|e| (400, e.to_string()))?;
+                let sign_tx = alias_signer.sign(&buf)?;
+
+                if let Some(path) = &self.audit_log {
+                    audit::append(path, &sign_tx.data, &format!("remote-signed {}", req.alias))
+                        .map_err(|e| (500, format!("audit log error: {}", e)))?;
+                }
+                let public_key = public_key_to_hex(&alias_signer.public_key)
+                    .map_err(|e| (500, e.to_string()))?;
+                Ok(json!(SignResponse {
+                    signature: hex::encode(&sign_tx.signature),
+                    public_key,
+                }))
+            }
+            _ => Err((404, "not found".to_string())),
+        }
+    }
+}
+
+/// Serve the signature requests, the keys are opened before listening
+pub fn serve(listen: &str, config: &ServerConfig) -> Result<()> {
+    let mut signers = BTreeMap::new();
+    for (alias, alias_config) in &config.signers {
+        let alias_signer = AliasSigner::open(alias_config)
+            .map_err(|e| format!("error opening signer `{}`: {}", alias, e))?;
+        signers.insert(alias.clone(), alias_signer);
+    }
+    let token = match &config.token_env {
+        Some(token_env) => Some(Zeroizing::new(
+            std::env::var(token_env).map_err(|_| format!("token not set in `{}`", token_env))?,
+        )),
+        None => None,
+    };
+    let signer_server = SignerServer {
+        signers,
+        audit_log: config.audit_log.clone(),
+        token,
+    };
+
+    let server = Server::http(listen)?;
+    eprintln!("Remote signer listening on {}", listen);
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .map_err(|_| "invalid header")?;
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match signer_server.handle(&mut request) {
+            Ok(body) => (200, body),
+            Err((status, error)) => {
+                eprintln!("{} {}: {}", request.url(), status, error);
+                (status, json!({ "error": error }))
+            }
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("Error answering the request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_config() {
+        let config = r##"{
+            "signers": {
+                "treasury": {
+                    "signer": { "type": "local" },
+                    "private_key_file": "/etc/trinci/treasury.key",
+                    "policy": { "methods": ["transfer"], "max_fuel": 1000 }
+                }
+            },
+            "audit_log": "/var/log/trinci-sign/audit.log",
+            "token_env": "SIGNER_TOKEN"
+        }"##;
+
+        let res = ServerConfig::from_json_slice(config.as_bytes()).unwrap();
+
+        let alias = &res.signers["treasury"];
+        assert_eq!(alias.signer, SignerConfig::Local);
+        assert_eq!(alias.policy.as_ref().unwrap().max_fuel, Some(1000));
+        assert_eq!(res.token_env.as_deref(), Some("SIGNER_TOKEN"));
+    }
+
+    #[test]
+    fn bearer_token() {
+        assert!(bearer_matches("Bearer secret", "secret"));
+        assert!(!bearer_matches("Bearer secreT", "secret"));
+        assert!(!bearer_matches("Bearer secret2", "secret"));
+        assert!(!bearer_matches("secret", "secret"));
+    }
+
+    #[test]
+    fn sign_request_json() {
+        let req = SignRequest {
+            alias: String::from("treasury"),
+            data: String::from("0102"),
+        };
+
+        let res = serde_json::to_string(&req).unwrap();
+
+        assert_eq!(res, r#"{"alias":"treasury","data":"0102"}"#);
+    }
+}
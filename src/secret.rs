@@ -0,0 +1,100 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Private key material, wiped from memory on drop and redacted in `Debug`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::types::Result;
+
+/// Base58 of the private key bytes array in pkcs8
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default, Zeroize, ZeroizeOnDrop)]
+#[serde(transparent)]
+pub struct PrivateKey(String);
+
+impl PrivateKey {
+    pub fn new(bs58_text: String) -> Self {
+        PrivateKey(bs58_text)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Decode the pkcs8 bytes, without intermediate copies left in memory
+    pub fn to_pkcs8(&self) -> Result<SecretBytes> {
+        // The decoded bytes are never longer than the base58 text,
+        // the buffer is not reallocated while decoding
+        let mut buf = SecretBytes(vec![0; self.0.len()]);
+        let len = bs58::decode(&self.0).onto(&mut buf.0[..])?;
+        buf.0.truncate(len);
+        Ok(buf)
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
+
+/// Secret bytes buffer
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretBytes(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_key_debug_redacted() {
+        let private_key = PrivateKey::new(String::from("5T"));
+
+        assert_eq!(format!("{:?}", private_key), "PrivateKey(<redacted>)");
+    }
+
+    #[test]
+    fn private_key_to_pkcs8() {
+        let private_key = PrivateKey::new(String::from("19p"));
+
+        let res = private_key.to_pkcs8().unwrap();
+
+        assert_eq!(res.as_bytes(), [0, 1, 255]);
+    }
+
+    #[test]
+    fn private_key_invalid_bs58() {
+        let private_key = PrivateKey::new(String::from("0OIl"));
+
+        assert!(private_key.to_pkcs8().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transaction signers.
+//!
+//! The signer is selected with a json config file:
+//! ```json
+//! { "type": "local" }
+//! { "type": "pkcs11", "module": "/usr/lib/softhsm/libsofthsm2.so", "token": "trinci", "key": "treasury" }
+//! { "type": "remote", "url": "http://signer.local:9000", "alias": "treasury" }
+//! ```
+//! The `local` signer uses the `private_key` of the unit tx args.
+
+use serde::{Deserialize, Serialize};
+use trinci_core::{
+    crypto::ecdsa::{CurveId, KeyPair as EcdsaKeyPair},
+    KeyPair, PublicKey,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    pkcs11::Pkcs11Signer,
+    remote_signer::{RemoteSigner, REMOTE_SIGNER_TOKEN_ENV},
+    secret::PrivateKey,
+    types::Result,
+};
+
+/// Default environment variable holding the PKCS#11 user PIN
+pub const PKCS11_PIN_ENV: &str = "TRINCI_PKCS11_PIN";
+
+/// Produces the signature of the serialized transaction data
+pub trait Signer {
+    /// Public key set as the transaction `caller`
+    fn public_key(&self) -> Result<PublicKey>;
+
+    /// ECDSA signature over the serialized transaction data
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SignerConfig {
+    Local,
+    Pkcs11 {
+        /// PKCS#11 module library path
+        module: String,
+        /// Label of the token holding the key, the first token if not given
+        token: Option<String>,
+        /// Label of the private key
+        key: String,
+        /// Environment variable holding the user PIN
+        #[serde(default = "default_pin_env")]
+        pin_env: String,
+    },
+    Remote {
+        /// Remote signer server url
+        url: String,
+        /// Alias of the key on the server
+        alias: String,
+        /// Environment variable holding the server token, if required
+        #[serde(default = "default_token_env")]
+        token_env: String,
+    },
+}
+
+fn default_pin_env() -> String {
+    PKCS11_PIN_ENV.to_string()
+}
+
+fn default_token_env() -> String {
+    REMOTE_SIGNER_TOKEN_ENV.to_string()
+}
+
+impl SignerConfig {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        serde_json::from_slice(buf).map_err(|e| e.into())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+
+    /// Create the configured signer, the local one uses the given private key
+    pub fn create_signer(&self, private_key: &PrivateKey) -> Result<Box<dyn Signer>> {
+        match self {
+            SignerConfig::Local => Ok(Box::new(LocalSigner::new(private_key)?)),
+            SignerConfig::Pkcs11 {
+                module,
+                token,
+                key,
+                pin_env,
+            } => {
+                let pin = std::env::var(pin_env)
+                    .map_err(|_| format!("PKCS#11 PIN not set in `{}`", pin_env))?;
+                Ok(Box::new(Pkcs11Signer::open(
+                    module,
+                    token.as_deref(),
+                    key,
+                    Zeroizing::new(pin),
+                )?))
+            }
+            SignerConfig::Remote {
+                url,
+                alias,
+                token_env,
+            } => {
+                let token = std::env::var(token_env).ok().map(Zeroizing::new);
+                Ok(Box::new(RemoteSigner::new(url, alias, token)))
+            }
+        }
+    }
+}
+
+/// In-process signer with the pkcs8 private key
+pub struct LocalSigner {
+    kp: KeyPair,
+}
+
+impl LocalSigner {
+    pub fn new(private_key: &PrivateKey) -> Result<Self> {
+        if private_key.is_empty() {
+            return Err("the private key is required by the local signer".into());
+        }
+        let private_bytes = private_key.to_pkcs8()?;
+        let kp = EcdsaKeyPair::from_pkcs8_bytes(CurveId::Secp384R1, private_bytes.as_bytes())?;
+        Ok(LocalSigner {
+            kp: KeyPair::Ecdsa(kp),
+        })
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key(&self) -> Result<PublicKey> {
+        Ok(self.kp.public_key())
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.kp.sign(data).map_err(|e| e.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signer_config_pkcs11() {
+        let config =
+            r#"{"type":"pkcs11","module":"/usr/lib/softhsm/libsofthsm2.so","key":"treasury"}"#;
+
+        let res = SignerConfig::from_json_slice(config.as_bytes()).unwrap();
+
+        assert_eq!(
+            res,
+            SignerConfig::Pkcs11 {
+                module: String::from("/usr/lib/softhsm/libsofthsm2.so"),
+                token: None,
+                key: String::from("treasury"),
+                pin_env: String::from(PKCS11_PIN_ENV),
+            }
+        );
+    }
+
+    #[test]
+    fn signer_config_unknown_type() {
+        let config = r#"{"type":"tpm"}"#;
+
+        assert!(SignerConfig::from_json_slice(config.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,390 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Unit tx args templates.
+//!
+//! A template is the json of the unit tx args, the private key excluded, where
+//! the string values may hold `{{name}}` or `{{name:type}}` placeholders:
+//! ```json
+//! {
+//!     "target": "#MYASSET",
+//!     "network": "SKYNET",
+//!     "fuel": "{{fuel:u64}}",
+//!     "contract": "",
+//!     "method": "transfer",
+//!     "args": { "from": "#TREASURY", "to": "{{to}}", "units": "{{units:u64}}" }
+//! }
+//! ```
+//! A string made of a single placeholder is replaced by the typed value,
+//! otherwise the value text is inserted. The values are checked against the
+//! placeholder type before the args are built.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use serde_json::{json, Value};
+
+use crate::{
+    secret::PrivateKey,
+    types::{Result, UnitTxArgs},
+};
+
+/// Values of the placeholders by name
+pub type Vars = BTreeMap<String, String>;
+
+/// Type of a placeholder value, `str` if not given
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VarType {
+    Str,
+    U64,
+    I64,
+    Bool,
+    /// HEX, encoded as messagepack bin
+    Bin,
+}
+
+impl FromStr for VarType {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        match text {
+            "str" => Ok(VarType::Str),
+            "u64" => Ok(VarType::U64),
+            "i64" => Ok(VarType::I64),
+            "bool" => Ok(VarType::Bool),
+            "bin" => Ok(VarType::Bin),
+            _ => Err(format!(
+                "unknown type `{}`, expected str, u64, i64, bool or bin",
+                text
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VarType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VarType::Str => "str",
+            VarType::U64 => "u64",
+            VarType::I64 => "i64",
+            VarType::Bool => "bool",
+            VarType::Bin => "bin",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl VarType {
+    /// Json of the checked value, with the typed hint for `bin`
+    fn to_json(self, value: &str) -> std::result::Result<Value, String> {
+        let invalid = |e: &dyn fmt::Display| format!("invalid {} value `{}`: {}", self, value, e);
+        match self {
+            VarType::Str => Ok(json!(value)),
+            VarType::U64 => value
+                .parse::<u64>()
+                .map(|v| json!(v))
+                .map_err(|e| invalid(&e)),
+            VarType::I64 => value
+                .parse::<i64>()
+                .map(|v| json!(v))
+                .map_err(|e| invalid(&e)),
+            VarType::Bool => value
+                .parse::<bool>()
+                .map(|v| json!(v))
+                .map_err(|e| invalid(&e)),
+            VarType::Bin => hex::decode(value)
+                .map(|_| json!({ "$bin": value }))
+                .map_err(|e| invalid(&e)),
+        }
+    }
+}
+
+/// Placeholder found in a template string
+#[derive(Debug, Clone, PartialEq)]
+struct Placeholder {
+    name: String,
+    var_type: VarType,
+    /// Byte range of `{{...}}` in the string
+    start: usize,
+    end: usize,
+}
+
+fn find_placeholders(text: &str) -> std::result::Result<Vec<Placeholder>, String> {
+    let mut placeholders = vec![];
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("{{").map(|i| i + offset) {
+        let end = match text[start..].find("}}") {
+            Some(i) => start + i + 2,
+            None => return Err(format!("unterminated placeholder in `{}`", text)),
+        };
+        let inner = text[start + 2..end - 2].trim();
+        let (name, var_type) = match inner.split_once(':') {
+            Some((name, var_type)) => (name.trim(), VarType::from_str(var_type.trim())?),
+            None => (inner, VarType::Str),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid placeholder name `{}`", name));
+        }
+        placeholders.push(Placeholder {
+            name: name.to_string(),
+            var_type,
+            start,
+            end,
+        });
+        offset = end;
+    }
+    Ok(placeholders)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template(Value);
+
+impl Template {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        match serde_json::from_slice(buf)? {
+            value @ Value::Object(_) => Ok(Template(value)),
+            _ => Err("the template must be a json object".into()),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+
+    /// Placeholders names and types, an error if a name has different types
+    pub fn placeholders(&self) -> Result<BTreeMap<String, VarType>> {
+        let mut strings = vec![];
+        collect_strings(&self.0, &mut strings);
+
+        let mut placeholders = BTreeMap::new();
+        for text in strings {
+            for placeholder in find_placeholders(text)? {
+                match placeholders.insert(placeholder.name.clone(), placeholder.var_type) {
+                    Some(var_type) if var_type != placeholder.var_type => {
+                        return Err(format!(
+                            "placeholder `{}` declared as {} and {}",
+                            placeholder.name, var_type, placeholder.var_type
+                        )
+                        .into())
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(placeholders)
+    }
+
+    /// Substitute the placeholders, every one must have a value and every
+    /// value a placeholder. All the invalid values are reported together.
+    pub fn fill(&self, vars: &Vars, private_key: PrivateKey) -> Result<UnitTxArgs> {
+        let placeholders = self.placeholders()?;
+        let mut errors: Vec<String> = placeholders
+            .keys()
+            .filter(|name| !vars.contains_key(*name))
+            .map(|name| format!("{}: missing value", name))
+            .chain(
+                vars.keys()
+                    .filter(|name| !placeholders.contains_key(*name))
+                    .map(|name| format!("{}: no such placeholder", name)),
+            )
+            .collect();
+        if errors.is_empty() {
+            for (name, var_type) in &placeholders {
+                if let Err(e) = var_type.to_json(&vars[name]) {
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("; ").into());
+        }
+
+        let value = substitute(&self.0, vars)?;
+        let mut args: UnitTxArgs = serde_json::from_value(value)
+            .map_err(|e| format!("invalid unit tx args from the template: {}", e))?;
+        if !private_key.is_empty() {
+            args.private_key = private_key;
+        }
+        Ok(args)
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, strings: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => strings.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, strings)),
+        Value::Object(map) => map.values().for_each(|item| collect_strings(item, strings)),
+        _ => (),
+    }
+}
+
+fn substitute(value: &Value, vars: &Vars) -> std::result::Result<Value, String> {
+    match value {
+        Value::String(text) => {
+            let placeholders = find_placeholders(text)?;
+            match placeholders.as_slice() {
+                [] => Ok(value.clone()),
+                [placeholder] if placeholder.start == 0 && placeholder.end == text.len() => {
+                    placeholder.var_type.to_json(&vars[&placeholder.name])
+                }
+                _ => {
+                    let mut res = String::new();
+                    let mut offset = 0;
+                    for placeholder in placeholders {
+                        res.push_str(&text[offset..placeholder.start]);
+                        res.push_str(&vars[&placeholder.name]);
+                        offset = placeholder.end;
+                    }
+                    res.push_str(&text[offset..]);
+                    Ok(Value::String(res))
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| substitute(item, vars))
+            .collect::<std::result::Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, item)| Ok((key.clone(), substitute(item, vars)?)))
+            .collect::<std::result::Result<_, String>>()
+            .map(Value::Object),
+        _ => Ok(value.clone()),
+    }
+}
+
+/// Parse a `--set` pair `name=value`
+pub fn parse_set(text: &str) -> Result<(String, String)> {
+    match text.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("invalid `{}`, expected name=value", text).into()),
+    }
+}
+
+/// Rows of a CSV with a header line, the header names are the vars names
+pub fn rows_from_csv(buf: &[u8]) -> Result<Vec<Vars>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(buf);
+    let headers = reader.headers()?.clone();
+
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record?;
+        rows.push(
+            headers
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        );
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r##"{
+        "target": "#MYASSET",
+        "network": "SKYNET",
+        "fuel": "{{fuel:u64}}",
+        "contract": "",
+        "method": "transfer",
+        "args": { "to": "{{to}}", "units": "{{units:u64}}", "memo": "payout {{month}} to {{to}}" }
+    }"##;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vars {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn template_fill() {
+        let template = Template::from_json_slice(TEMPLATE.as_bytes()).unwrap();
+        let values = vars(&[
+            ("fuel", "1000"),
+            ("to", "#BOB"),
+            ("units", "10"),
+            ("month", "May"),
+        ]);
+
+        let res = template.fill(&values, PrivateKey::default()).unwrap();
+
+        assert_eq!(res.fuel, 1000);
+        let args = serde_json::to_value(&res.args).unwrap();
+        assert_eq!(
+            args,
+            json!({"to": "#BOB", "units": 10, "memo": "payout May to #BOB"})
+        );
+    }
+
+    #[test]
+    fn template_fill_errors() {
+        let template = Template::from_json_slice(TEMPLATE.as_bytes()).unwrap();
+        let values = vars(&[
+            ("fuel", "1000"),
+            ("to", "#BOB"),
+            ("units", "ten"),
+            ("month", "May"),
+        ]);
+
+        let err = template
+            .fill(&values, PrivateKey::default())
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.starts_with("units: invalid u64 value `ten`"));
+
+        let values = vars(&[("fuel", "1000"), ("too", "#BOB")]);
+        let err = template
+            .fill(&values, PrivateKey::default())
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            err,
+            "month: missing value; to: missing value; units: missing value; too: no such placeholder"
+        );
+    }
+
+    #[test]
+    fn placeholder_conflicting_types() {
+        let template =
+            Template::from_json_slice(br#"{"target":"{{a}}","method":"{{a:u64}}"}"#).unwrap();
+
+        assert!(template.placeholders().is_err());
+    }
+
+    #[test]
+    fn csv_rows() {
+        let res = rows_from_csv(b"account, units\n#ALICE, 10\n#BOB,20\n").unwrap();
+
+        assert_eq!(
+            res,
+            vec![
+                vars(&[("account", "#ALICE"), ("units", "10")]),
+                vars(&[("account", "#BOB"), ("units", "20")]),
+            ]
+        );
+    }
+}
@@ -19,24 +19,153 @@ use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use zeroize::Zeroizing;
+
+use crate::{
+    abi::AbiRegistry, audit::Checkpoint, fuel::FuelSetting, mock_node::Reply,
+    multisig::MultisigCommand, payout::PayoutConfig, policy::SigningPolicy,
+    remote_signer::ServerConfig, secret::PrivateKey, signer::SignerConfig, validation::FuelBounds,
+    wizard::Profile,
+};
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 pub struct AppCommand {
     pub operation: AppOperation,
     pub args: Arguments,
     pub url: String,
+    pub options: AppOptions,
+}
+
+/// Optional behaviours of the subcommands
+#[derive(Default)]
+pub struct AppOptions {
+    /// Skip the unit tx args validation (an invalid contract is ignored)
+    pub lenient: bool,
+    /// Fuel limits accepted by the validation
+    pub fuel_bounds: FuelBounds,
+    /// Fuel limit in place of the args one
+    pub fuel: Option<FuelSetting>,
+    /// History of the burned fuel, read by the automatic limit and written with the receipts
+    pub fuel_history: Option<String>,
+    /// Contracts interface descriptors
+    pub abi: Option<AbiRegistry>,
+    /// Restrictions on what the key may sign
+    pub policy: Option<SigningPolicy>,
+    /// Audit log file where the produced signatures are recorded
+    pub audit_log: Option<String>,
+    /// Signer backend, the in-process one with the args private key if not given
+    pub signer: Option<SignerConfig>,
+    /// Check that the transaction network matches the node one before submitting
+    pub check_network: bool,
+    /// Validate and sign the transaction without sending it
+    pub dry_run: bool,
+    /// Encoding of the produced bytes, `None` for the subcommand default
+    pub encoding: Option<Encoding>,
+    /// File where the output is written in place of the stdout
+    pub out: Option<String>,
+}
+
+/// Output encoding of the produced bytes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Raw,
+    Hex,
+    Bs58,
+    Base64,
+    /// Json byte array, eg: `[1,2,3]`
+    Bytes,
+}
+
+impl Encoding {
+    pub const VARIANTS: [&'static str; 5] = ["raw", "hex", "bs58", "base64", "bytes"];
+
+    pub fn encode(&self, buf: &[u8]) -> Vec<u8> {
+        match self {
+            Encoding::Raw => buf.to_vec(),
+            Encoding::Hex => hex::encode(buf).into_bytes(),
+            Encoding::Bs58 => bs58::encode(buf).into_string().into_bytes(),
+            Encoding::Base64 => base64::encode(buf).into_bytes(),
+            Encoding::Bytes => format!("{:?}", buf).replace(' ', "").into_bytes(),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = ();
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        match input {
+            "raw" => Ok(Encoding::Raw),
+            "hex" => Ok(Encoding::Hex),
+            "bs58" => Ok(Encoding::Bs58),
+            "base64" => Ok(Encoding::Base64),
+            "bytes" => Ok(Encoding::Bytes),
+            _ => Err(()),
+        }
+    }
 }
 
 pub enum Arguments {
+    /// Subcommands taking only the node url
+    None,
     UnitTxArgsType(UnitTxArgs),
+    /// Args of the template rows, a transaction each
+    UnitTxBatchArgs(Vec<UnitTxArgs>),
     MsgPackString(String),
     MsgPackStruct(Value),
+    MsgPackBytes(Vec<u8>),
+    GetBlockArgs {
+        height: u64,
+        txs: bool,
+    },
+    GetTxArgs {
+        hash: String,
+    },
+    GetReceiptArgs {
+        hash: String,
+    },
+    MultisigArgs(MultisigCommand),
+    AuditVerifyArgs {
+        log: String,
+        checkpoint: Option<Checkpoint>,
+    },
+    SignerServerArgs {
+        listen: String,
+        config: ServerConfig,
+    },
+    SelftestArgs {
+        vectors: Option<String>,
+        generate: bool,
+    },
+    InteractiveArgs {
+        profile: Option<Profile>,
+    },
+    MockNodeArgs {
+        listen: String,
+        scenario: Vec<Reply>,
+    },
+    PayoutArgs(PayoutConfig),
 }
 
 pub enum AppOperation {
     CreateUnitTx,
     SubmitUnitTx,
     ToMessagePack,
+    FromMessagePack,
+    GetBlock,
+    GetTx,
+    GetReceipt,
+    NodeInfo,
+    Multisig,
+    Audit,
+    SignerServer,
+    MockNode,
+    Selftest,
+    EncodeArgs,
+    DecodeArgs,
+    Wizard,
+    Repl,
+    Payout,
 }
 
 impl FromStr for AppOperation {
@@ -47,11 +176,60 @@ impl FromStr for AppOperation {
             "create_unit_tx" => Ok(AppOperation::CreateUnitTx),
             "submit_unit_tx" => Ok(AppOperation::SubmitUnitTx),
             "to_message_pack" => Ok(AppOperation::ToMessagePack),
+            "from_message_pack" => Ok(AppOperation::FromMessagePack),
+            "get_block" => Ok(AppOperation::GetBlock),
+            "get_tx" => Ok(AppOperation::GetTx),
+            "get_receipt" => Ok(AppOperation::GetReceipt),
+            "node_info" => Ok(AppOperation::NodeInfo),
+            "multisig" => Ok(AppOperation::Multisig),
+            "audit" => Ok(AppOperation::Audit),
+            "signer_server" => Ok(AppOperation::SignerServer),
+            "mock_node" => Ok(AppOperation::MockNode),
+            "selftest" => Ok(AppOperation::Selftest),
+            "encode_args" => Ok(AppOperation::EncodeArgs),
+            "decode_args" => Ok(AppOperation::DecodeArgs),
+            "wizard" => Ok(AppOperation::Wizard),
+            "repl" => Ok(AppOperation::Repl),
+            "payout" => Ok(AppOperation::Payout),
             _ => Err(()),
         }
     }
 }
 
+/// Information reported by a Trinci node
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct NodeInfo {
+    pub network: Option<String>,
+    pub height: Option<u64>,
+    pub pending_txs: Option<usize>,
+    pub version: Option<String>,
+}
+
+impl NodeInfo {
+    /// Fill the missing fields with the ones found in a node json answer
+    /// (e.g. the `/visa` or `/status` endpoints)
+    pub fn update_from_json(&mut self, value: &Value) {
+        const NETWORK_PTRS: [&str; 3] = ["/network", "/network_name", "/network_config/name"];
+        const VERSION_PTRS: [&str; 3] = ["/version", "/node_version", "/node/version"];
+
+        let find_str = |ptrs: &[&str]| {
+            ptrs.iter()
+                .find_map(|ptr| value.pointer(ptr).and_then(Value::as_str))
+                .map(str::to_string)
+        };
+
+        if self.network.is_none() {
+            self.network = find_str(&NETWORK_PTRS[..]);
+        }
+        if self.version.is_none() {
+            self.version = find_str(&VERSION_PTRS[..]);
+        }
+        if self.height.is_none() {
+            self.height = value.pointer("/height").and_then(Value::as_u64);
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct UnitTxArgs {
     pub target: String,
@@ -60,7 +238,9 @@ pub struct UnitTxArgs {
     pub contract: String,
     pub method: String,
     pub args: serde_value::Value,
-    pub private_key: String, // base58 of a bytes array
+    /// Not required with a signer backend holding the key
+    #[serde(default)]
+    pub private_key: PrivateKey,
 }
 
 impl UnitTxArgs {
@@ -71,22 +251,28 @@ impl UnitTxArgs {
         }
     }
 
+    /// Messagepack accepted by `from_msgpack`, `from_hex_string` and `from_bs58_string`
+    pub fn to_msgpack(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| e.into())
+    }
+
+    pub fn from_msgpack(buf: &[u8]) -> Option<Self> {
+        match rmp_serde::from_slice::<UnitTxArgs>(buf) {
+            Ok(val) => Some(val),
+            Err(_) => None,
+        }
+    }
+
     pub fn from_hex_string(hex_text: &str) -> Option<Self> {
         match hex::decode(hex_text) {
-            Ok(buf) => match rmp_serde::from_slice::<UnitTxArgs>(&buf) {
-                Ok(val) => Some(val),
-                Err(_) => None,
-            },
+            Ok(buf) => Self::from_msgpack(&Zeroizing::new(buf)),
             Err(_) => None,
         }
     }
 
     pub fn from_bs58_string(bs58_text: &str) -> Option<Self> {
         match bs58::decode(bs58_text).into_vec() {
-            Ok(buf) => match rmp_serde::from_slice::<UnitTxArgs>(&buf) {
-                Ok(val) => Some(val),
-                Err(_) => None,
-            },
+            Ok(buf) => Self::from_msgpack(&Zeroizing::new(buf)),
             Err(_) => None,
         }
     }
@@ -109,10 +295,40 @@ mod tests {
             contract: String::from("12205ac0cff189e22794b847687578ef4714c7da10fe9e6f6e313649286361b0827f"),
             method: String::from("my_cool_method"),
             args: serde_value::value!("args_for_contract"),
-            private_key: String::from("invalidgtJKh4e3cwBDmgSHANXb7hrxmR4VeJUkwLbzkAtZbmmcPeSLBm3GkrrRNr5Xzr3vjYs5sxEyUqpEF7b2ckgCjCPpEp5wVLAtcuUZKiWk8Z37L3BwiuXKW6JWYsvPCJAHfYpGJ7mXrQiPPb2GgPn9pwMFTS581tYya85ct5w8"),
+            private_key: PrivateKey::new(String::from("invalidgtJKh4e3cwBDmgSHANXb7hrxmR4VeJUkwLbzkAtZbmmcPeSLBm3GkrrRNr5Xzr3vjYs5sxEyUqpEF7b2ckgCjCPpEp5wVLAtcuUZKiWk8Z37L3BwiuXKW6JWYsvPCJAHfYpGJ7mXrQiPPb2GgPn9pwMFTS581tYya85ct5w8")),
         }
     }
 
+    #[test]
+    fn encoding_encode() {
+        let buf = [0, 1, 255];
+
+        assert_eq!(Encoding::Raw.encode(&buf), buf);
+        assert_eq!(Encoding::Hex.encode(&buf), b"0001ff");
+        assert_eq!(Encoding::Bs58.encode(&buf), b"19p");
+        assert_eq!(Encoding::Base64.encode(&buf), b"AAH/");
+        assert_eq!(Encoding::Bytes.encode(&buf), b"[0,1,255]");
+    }
+
+    #[test]
+    fn node_info_from_json() {
+        let mut info = NodeInfo {
+            height: Some(5),
+            ..Default::default()
+        };
+        let visa = serde_json::json!({
+            "network_config": { "name": "SKYNET" },
+            "node_version": "0.2.7",
+            "height": 3,
+        });
+
+        info.update_from_json(&visa);
+
+        assert_eq!(info.network, Some(String::from("SKYNET")));
+        assert_eq!(info.version, Some(String::from("0.2.7")));
+        assert_eq!(info.height, Some(5));
+    }
+
     #[test]
     fn unit_tx_args_from_hex() {
         let expected = create_unit_tx_args();
@@ -131,6 +347,28 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[test]
+    fn unit_tx_args_from_msgpack() {
+        let expected = create_unit_tx_args();
+        let buf = hex::decode(ARGS_HEX).unwrap();
+
+        let res = UnitTxArgs::from_msgpack(&buf).unwrap();
+
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn unit_tx_args_to_msgpack() {
+        let args = create_unit_tx_args();
+
+        let buf = args.to_msgpack().unwrap();
+
+        assert_eq!(
+            UnitTxArgs::from_hex_string(&hex::encode(buf)).unwrap(),
+            args
+        );
+    }
+
     #[test]
     fn unit_tx_args_from_bs58() {
         let expected = create_unit_tx_args();
@@ -0,0 +1,204 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+use trinci_core::crypto::Hash;
+
+use crate::types::{Result, UnitTxArgs};
+
+/// Fuel limits accepted for a transaction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuelBounds {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl Default for FuelBounds {
+    fn default() -> Self {
+        FuelBounds {
+            min: 1,
+            max: u64::MAX,
+        }
+    }
+}
+
+/// Check that the text is a well formed account id: a `#` name, made of
+/// ASCII letters, digits, `_`, `-` and `.`, or the base58 of a multihash
+pub fn check_account_id(account_id: &str) -> std::result::Result<(), String> {
+    if account_id.is_empty() {
+        return Err("empty account id".to_string());
+    }
+    if let Some(name) = account_id.strip_prefix('#') {
+        if name.is_empty() {
+            return Err("empty account name".to_string());
+        }
+        return match name
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        {
+            Some(c) => Err(format!("invalid character {:?} in account id", c)),
+            None => Ok(()),
+        };
+    }
+    match bs58::decode(account_id).into_vec() {
+        Ok(buf) if Hash::from_bytes(&buf).is_ok() => Ok(()),
+        _ => Err(format!(
+            "`{}` is neither a #name nor a base58 multihash",
+            account_id
+        )),
+    }
+}
+
+/// Check that the text is the hex of a valid multihash
+pub fn check_contract(contract: &str) -> std::result::Result<(), String> {
+    match Hash::from_hex(contract) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("`{}` is not a valid multihash", contract)),
+    }
+}
+
+/// Check the unit tx arguments, collecting an error message for every invalid field
+pub fn validate_unit_tx_args(args: &UnitTxArgs, fuel_bounds: &FuelBounds) -> Result<()> {
+    let mut errors = vec![];
+
+    if let Err(e) = check_account_id(&args.target) {
+        errors.push(format!("target: {}", e));
+    }
+    if args.network.is_empty() {
+        errors.push("network: must not be empty".to_string());
+    }
+    if args.fuel < fuel_bounds.min || args.fuel > fuel_bounds.max {
+        errors.push(format!(
+            "fuel: {} is out of bounds [{}, {}]",
+            args.fuel, fuel_bounds.min, fuel_bounds.max
+        ));
+    }
+    // An empty contract means the account default one
+    if !args.contract.is_empty() {
+        if let Err(e) = check_contract(&args.contract) {
+            errors.push(format!("contract: {}", e));
+        }
+    }
+    if args.method.is_empty() {
+        errors.push("method: must not be empty".to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; ").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::PrivateKey;
+
+    fn create_unit_tx_args() -> UnitTxArgs {
+        UnitTxArgs {
+            target: String::from("#ACCOUNT"),
+            network: String::from("SKYNET"),
+            fuel: 10000u64,
+            contract: String::from(
+                "12205ac0cff189e22794b847687578ef4714c7da10fe9e6f6e313649286361b0827f",
+            ),
+            method: String::from("my_cool_method"),
+            args: serde_value::value!("args_for_contract"),
+            private_key: PrivateKey::default(),
+        }
+    }
+
+    #[test]
+    fn valid_unit_tx_args() {
+        let args = create_unit_tx_args();
+
+        assert!(validate_unit_tx_args(&args, &FuelBounds::default()).is_ok());
+    }
+
+    #[test]
+    fn valid_unit_tx_args_without_contract() {
+        let mut args = create_unit_tx_args();
+        args.contract = String::new();
+
+        assert!(validate_unit_tx_args(&args, &FuelBounds::default()).is_ok());
+    }
+
+    #[test]
+    fn invalid_unit_tx_args() {
+        let mut args = create_unit_tx_args();
+        args.target = String::from("#MY ACCOUNT");
+        args.fuel = 0;
+        args.contract = String::from("12205ac0");
+        args.method = String::new();
+
+        let err = validate_unit_tx_args(&args, &FuelBounds::default())
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            err,
+            "target: invalid character ' ' in account id; \
+             fuel: 0 is out of bounds [1, 18446744073709551615]; \
+             contract: `12205ac0` is not a valid multihash; \
+             method: must not be empty"
+        );
+    }
+
+    #[test]
+    fn valid_account_ids() {
+        for account_id in [
+            "#ACCOUNT",
+            "#my_asset-2.0",
+            "QmYHnEQLdf5h7KYbjFPuHSRk2SPgdXrJWFh5W696HPfq7i",
+        ] {
+            assert_eq!(check_account_id(account_id), Ok(()), "{}", account_id);
+        }
+    }
+
+    #[test]
+    fn invalid_account_ids() {
+        let cases = [
+            ("", "empty account id"),
+            ("#", "empty account name"),
+            ("#foo!!", "invalid character '!' in account id"),
+            ("#MY ACCOUNT", "invalid character ' ' in account id"),
+            ("foo!!", "`foo!!` is neither a #name nor a base58 multihash"),
+            // Valid base58, not a multihash
+            ("MyAccount", "`MyAccount` is neither a #name nor a base58 multihash"),
+            // Sha256 multihash with a truncated digest
+            (
+                "6PKsUU42H1ULxoo8R5Aikbm82gjkR8txAoNmX9sKR73TJ",
+                "`6PKsUU42H1ULxoo8R5Aikbm82gjkR8txAoNmX9sKR73TJ` is neither a #name nor a base58 multihash",
+            ),
+        ];
+        for (account_id, expected) in cases {
+            assert_eq!(check_account_id(account_id), Err(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn unit_tx_args_fuel_out_of_bounds() {
+        let args = create_unit_tx_args();
+        let fuel_bounds = FuelBounds { min: 1, max: 1000 };
+
+        let err = validate_unit_tx_args(&args, &fuel_bounds)
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(err, "fuel: 10000 is out of bounds [1, 1000]");
+    }
+}
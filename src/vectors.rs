@@ -0,0 +1,124 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Golden test vectors of the unit transactions signing.
+//!
+//! Every vector holds the unit tx args, with a test private key, and the fixed
+//! nonce. The `expected` fields are produced by `selftest --generate`, so other
+//! implementations can check their bytes against the same inputs.
+//! The ECDSA signature is randomized: it is verified, not compared.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Result, UnitTxArgs};
+
+/// Vectors set checked in the repository
+pub const DEFAULT_VECTORS: &str = include_str!("../vectors/unit_tx.json");
+
+/// Bytes produced from the vector inputs, all HEX
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Expected {
+    /// Messagepacked caller public key
+    pub public_key: String,
+    /// Serialized `TransactionData`, the signed bytes
+    pub data: String,
+    /// Transaction hash (multihash)
+    pub hash: String,
+    /// Messagepacked `PutTransactionRequest` sent to the node
+    pub message: String,
+    /// A signature of `data`
+    pub signature: String,
+}
+
+impl Expected {
+    /// Compare with the derived bytes, the signature excluded
+    pub fn compare(&self, derived: &Expected) -> Vec<String> {
+        let fields = [
+            ("public_key", &self.public_key, &derived.public_key),
+            ("data", &self.data, &derived.data),
+            ("hash", &self.hash, &derived.hash),
+            ("message", &self.message, &derived.message),
+        ];
+        fields
+            .iter()
+            .filter(|(_, expected, derived)| expected != derived)
+            .map(|(name, expected, derived)| {
+                format!("{}: expected {}, got {}", name, expected, derived)
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TestVector {
+    pub name: String,
+    pub args: UnitTxArgs,
+    /// Transaction nonce HEX
+    pub nonce: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Expected>,
+}
+
+pub fn from_json_str(json_text: &str) -> Result<Vec<TestVector>> {
+    serde_json::from_str(json_text).map_err(|e| e.into())
+}
+
+pub fn load(path: &str) -> Result<Vec<TestVector>> {
+    from_json_str(&std::fs::read_to_string(path)?)
+}
+
+pub fn save(path: &str, vectors: &[TestVector]) -> Result<()> {
+    let mut text = serde_json::to_string_pretty(vectors)?;
+    text.push('\n');
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_vectors_parse() {
+        let res = from_json_str(DEFAULT_VECTORS).unwrap();
+
+        assert!(!res.is_empty());
+        assert!(res.iter().all(|vector| !vector.args.private_key.is_empty()));
+    }
+
+    #[test]
+    fn expected_compare() {
+        let expected = Expected {
+            public_key: String::from("aa"),
+            data: String::from("bb"),
+            hash: String::from("cc"),
+            message: String::from("dd"),
+            signature: String::from("ee"),
+        };
+        let derived = Expected {
+            data: String::from("b0"),
+            signature: String::from("ff"),
+            ..expected.clone()
+        };
+
+        let res = expected.compare(&derived);
+
+        assert_eq!(res, vec![String::from("data: expected bb, got b0")]);
+    }
+}
@@ -0,0 +1,470 @@
+// This file is part of TRINCI.
+//
+// Copyright (C) 2021 Affidaty Spa.
+//
+// TRINCI is free software: you can redistribute it and/or modify it under
+// the terms of the GNU Affero General Public License as published by the
+// Free Software Foundation, either version 3 of the License, or (at your
+// option) any later version.
+//
+// TRINCI is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+// FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License
+// for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with TRINCI. If not, see <https://www.gnu.org/licenses/>.
+
+//! Interactive prompts of the `wizard` and `repl` subcommands.
+//!
+//! The profile file holds the defaults proposed by the prompts:
+//! ```json
+//! {
+//!     "url": "http://localhost:8000/api/v1",
+//!     "network": "SKYNET",
+//!     "fuel": 1000,
+//!     "abi": "abi.json",
+//!     "signer": { "type": "pkcs11", "module": "/usr/lib/softhsm/libsofthsm2.so", "key": "treasury" },
+//!     "private_key_file": "treasury.key",
+//!     "policy": "policy.json",
+//!     "audit_log": "audit.log",
+//!     "fuel_history": "fuel.jsonl"
+//! }
+//! ```
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_value::Value;
+use zeroize::Zeroizing;
+
+use crate::{
+    abi::{AbiRegistry, TypeSchema},
+    secret::PrivateKey,
+    signer::SignerConfig,
+    types::{Result, UnitTxArgs},
+    validation::{check_account_id, check_contract},
+};
+
+/// Defaults of the interactive prompts, every field is optional
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Trinci node url
+    pub url: Option<String>,
+    pub network: Option<String>,
+    pub fuel: Option<u64>,
+    /// Contracts interface descriptors file
+    pub abi: Option<String>,
+    /// Signer backend, the `private_key_file` is used if not given
+    pub signer: Option<SignerConfig>,
+    /// File with the base58 private key
+    pub private_key_file: Option<String>,
+    /// Signing policy file
+    pub policy: Option<String>,
+    /// Audit log file
+    pub audit_log: Option<String>,
+    /// History file where the fuel burned by the submitted transactions is recorded
+    pub fuel_history: Option<String>,
+}
+
+impl Profile {
+    pub fn from_json_slice(buf: &[u8]) -> Result<Self> {
+        serde_json::from_slice(buf).map_err(|e| e.into())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_json_slice(&std::fs::read(path)?)
+    }
+}
+
+/// Line oriented prompts, the answers are read from `input`
+pub struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Prompter { input, output }
+    }
+
+    /// Print a line on the prompts output
+    pub fn say(&mut self, text: &str) -> Result<()> {
+        writeln!(self.output, "{}", text)?;
+        Ok(())
+    }
+
+    /// Read the trimmed answer, an error if the input is closed
+    fn read_answer(&mut self) -> Result<String> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err("input closed".into());
+        }
+        Ok(line.trim().to_string())
+    }
+
+    /// Ask until an answer, or the default, is accepted by `parse`
+    pub fn ask_with<T>(
+        &mut self,
+        label: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> std::result::Result<T, String>,
+    ) -> Result<T> {
+        loop {
+            match default {
+                Some(default) => write!(self.output, "{} [{}]: ", label, default)?,
+                None => write!(self.output, "{}: ", label)?,
+            }
+            self.output.flush()?;
+
+            let answer = self.read_answer()?;
+            let answer = match (answer.is_empty(), default) {
+                (true, Some(default)) => default.to_string(),
+                _ => answer,
+            };
+            match parse(&answer) {
+                Ok(value) => return Ok(value),
+                Err(e) => self.say(&format!("Invalid value: {}", e))?,
+            }
+        }
+    }
+
+    /// Ask a non empty text
+    pub fn ask(&mut self, label: &str, default: Option<&str>) -> Result<String> {
+        self.ask_with(label, default, |answer| match answer {
+            "" => Err("must not be empty".to_string()),
+            answer => Ok(answer.to_string()),
+        })
+    }
+
+    /// Ask an optional text, `None` if empty and without a default
+    pub fn ask_optional(&mut self, label: &str, default: Option<&str>) -> Result<Option<String>> {
+        self.ask_with(label, default, |answer| match answer {
+            "" => Ok(None),
+            answer => Ok(Some(answer.to_string())),
+        })
+    }
+
+    /// Ask a yes/no question, no by default
+    pub fn confirm(&mut self, label: &str) -> Result<bool> {
+        self.ask_with(label, Some("n"), |answer| {
+            match answer.to_lowercase().as_str() {
+                "y" | "yes" => Ok(true),
+                "n" | "no" => Ok(false),
+                _ => Err("answer y or n".to_string()),
+            }
+        })
+    }
+}
+
+/// The given profile, or the one of the file asked
+pub fn ask_profile<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    profile: Option<Profile>,
+) -> Result<Profile> {
+    if let Some(profile) = profile {
+        return Ok(profile);
+    }
+    match prompter.ask_optional("Profile file (empty for none)", None)? {
+        Some(path) => Profile::load(&path),
+        None => Ok(Profile::default()),
+    }
+}
+
+fn parse_json_value(answer: &str) -> std::result::Result<Value, String> {
+    serde_json::from_str::<Value>(answer).map_err(|e| e.to_string())
+}
+
+fn parse_hex_bin(answer: &str) -> std::result::Result<Value, String> {
+    hex::decode(answer)
+        .map(Value::Bytes)
+        .map_err(|e| e.to_string())
+}
+
+/// Ask a contract value of the declared type, the maps fields are asked one by one.
+/// The values without a simple type are asked as json, with the typed hints.
+pub fn ask_value<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    label: &str,
+    schema: &TypeSchema,
+) -> Result<Value> {
+    let label = format!("{} ({})", label, type_name(schema));
+    let value = match schema {
+        TypeSchema::Nil => Value::Unit,
+        TypeSchema::Bool => prompter.ask_with(&label, None, |answer| {
+            answer
+                .parse::<bool>()
+                .map(Value::Bool)
+                .map_err(|_| "answer true or false".to_string())
+        })?,
+        TypeSchema::U64 => prompter.ask_with(&label, None, |answer| {
+            answer
+                .parse::<u64>()
+                .map(Value::U64)
+                .map_err(|e| e.to_string())
+        })?,
+        TypeSchema::I64 => prompter.ask_with(&label, None, |answer| {
+            answer
+                .parse::<i64>()
+                .map(Value::I64)
+                .map_err(|e| e.to_string())
+        })?,
+        TypeSchema::F64 => prompter.ask_with(&label, None, |answer| {
+            answer
+                .parse::<f64>()
+                .map(Value::F64)
+                .map_err(|e| e.to_string())
+        })?,
+        TypeSchema::String => {
+            prompter.ask_with(&label, None, |answer| Ok(Value::String(answer.to_string())))?
+        }
+        TypeSchema::Bin => prompter.ask_with(&label, None, parse_hex_bin)?,
+        TypeSchema::Map { fields, optional } => {
+            let mut map = std::collections::BTreeMap::new();
+            for (name, field_schema) in fields {
+                if optional.contains(name)
+                    && !prompter.confirm(&format!("Set the optional `{}`", name))?
+                {
+                    continue;
+                }
+                let value = ask_value(prompter, name, field_schema)?;
+                map.insert(Value::String(name.clone()), value);
+            }
+            Value::Map(map)
+        }
+        TypeSchema::Any | TypeSchema::Array { .. } => {
+            prompter.ask_with(&label, None, parse_json_value)?
+        }
+    };
+    Ok(value)
+}
+
+fn type_name(schema: &TypeSchema) -> &'static str {
+    match schema {
+        TypeSchema::Any => "json",
+        TypeSchema::Nil => "nil",
+        TypeSchema::Bool => "bool",
+        TypeSchema::U64 => "u64",
+        TypeSchema::I64 => "i64",
+        TypeSchema::F64 => "f64",
+        TypeSchema::String => "string",
+        TypeSchema::Bin => "HEX",
+        TypeSchema::Array { .. } => "json array",
+        TypeSchema::Map { .. } => "map",
+    }
+}
+
+/// Ask the signer and the unit tx args.
+/// The private key is read from a file, never typed on the terminal.
+pub fn ask_unit_tx<R: BufRead, W: Write>(
+    prompter: &mut Prompter<R, W>,
+    profile: &Profile,
+    abi: Option<&AbiRegistry>,
+) -> Result<(UnitTxArgs, Option<SignerConfig>)> {
+    let signer =
+        match prompter.ask_optional("Signer config file (empty for the private key file)", None)? {
+            Some(path) => Some(SignerConfig::load(&path)?),
+            None => profile.signer.clone(),
+        };
+    let private_key = match &signer {
+        None | Some(SignerConfig::Local) => {
+            let path = prompter.ask("Private key file", profile.private_key_file.as_deref())?;
+            let text = Zeroizing::new(std::fs::read_to_string(path)?);
+            PrivateKey::new(text.trim().to_string())
+        }
+        Some(_) => PrivateKey::default(),
+    };
+
+    let target = prompter.ask_with("Target account", None, |answer| {
+        check_account_id(answer).map(|_| answer.to_string())
+    })?;
+    let network = prompter.ask("Network", profile.network.as_deref())?;
+    let fuel = prompter.ask_with(
+        "Fuel limit",
+        profile.fuel.map(|fuel| fuel.to_string()).as_deref(),
+        |answer| match answer.parse::<u64>() {
+            Ok(0) => Err("must be greater than 0".to_string()),
+            Ok(fuel) => Ok(fuel),
+            Err(e) => Err(e.to_string()),
+        },
+    )?;
+    let contract = prompter.ask_with(
+        "Contract multihash (empty for the account one)",
+        None,
+        |answer| match answer {
+            "" => Ok(String::new()),
+            answer => check_contract(answer).map(|_| answer.to_string()),
+        },
+    )?;
+    let method = prompter.ask("Method", None)?;
+
+    let method_abi = match abi {
+        Some(abi) => abi.method(&contract, &method)?,
+        None => None,
+    };
+    let args = match method_abi {
+        Some(method_abi) => ask_value(prompter, "Args", &method_abi.args)?,
+        None => prompter.ask_with("Args (json, with the typed hints)", None, parse_json_value)?,
+    };
+
+    let args = UnitTxArgs {
+        target,
+        network,
+        fuel,
+        contract,
+        method,
+        args,
+        private_key,
+    };
+    Ok((args, signer))
+}
+
+/// Commands of the `repl` session
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplCommand {
+    Help,
+    Exit,
+    NodeInfo,
+    GetBlock {
+        height: u64,
+        txs: bool,
+    },
+    GetTx {
+        hash: String,
+    },
+    GetReceipt {
+        hash: String,
+    },
+    /// Build, preview and submit a transaction with the wizard
+    Tx,
+}
+
+pub const REPL_HELP: &str = "Commands:
+  node_info                  network, height, pending transactions and version of the node
+  get_block <HEIGHT> [txs]   block header, with the transactions hashes with `txs`
+  get_tx <HASH>              transaction
+  get_receipt <HASH>         transaction receipt
+  tx                         build, preview and submit a transaction
+  help                       this message
+  exit                       end the session";
+
+impl ReplCommand {
+    pub fn parse(line: &str) -> std::result::Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["help"] => Ok(ReplCommand::Help),
+            ["exit"] | ["quit"] => Ok(ReplCommand::Exit),
+            ["node_info"] => Ok(ReplCommand::NodeInfo),
+            ["get_block", height] | ["get_block", height, "txs"] => Ok(ReplCommand::GetBlock {
+                height: height
+                    .parse()
+                    .map_err(|_| format!("invalid height `{}`", height))?,
+                txs: words.len() == 3,
+            }),
+            ["get_tx", hash] => Ok(ReplCommand::GetTx {
+                hash: hash.to_string(),
+            }),
+            ["get_receipt", hash] => Ok(ReplCommand::GetReceipt {
+                hash: hash.to_string(),
+            }),
+            ["tx"] => Ok(ReplCommand::Tx),
+            _ => Err(format!("unknown command `{}`, type `help`", line.trim())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn prompter(answers: &str) -> Prompter<Cursor<Vec<u8>>, Vec<u8>> {
+        Prompter::new(Cursor::new(answers.as_bytes().to_vec()), vec![])
+    }
+
+    #[test]
+    fn ask_with_retries_and_default() {
+        let mut prompter = prompter("abc\n\n");
+
+        let res = prompter
+            .ask_with("Fuel", Some("1000"), |answer| {
+                answer.parse::<u64>().map_err(|e| e.to_string())
+            })
+            .unwrap();
+
+        assert_eq!(res, 1000);
+        let output = String::from_utf8(prompter.output).unwrap();
+        assert!(output.contains("Invalid value"));
+    }
+
+    #[test]
+    fn ask_input_closed() {
+        let mut prompter = prompter("");
+
+        assert!(prompter.ask("Method", None).is_err());
+    }
+
+    #[test]
+    fn ask_unit_tx_with_abi() {
+        let abi = AbiRegistry::from_json_slice(
+            br#"{"12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118":{"methods":{"transfer":{"args":{"type":"map","fields":{"to":{"type":"string"},"units":{"type":"u64"},"memo":{"type":"string"}},"optional":["memo"]}}}}}"#,
+        )
+        .unwrap();
+        let profile = Profile {
+            network: Some(String::from("SKYNET")),
+            signer: Some(SignerConfig::Remote {
+                url: String::from("http://signer.local:9000"),
+                alias: String::from("treasury"),
+                token_env: String::from("TOKEN"),
+            }),
+            ..Default::default()
+        };
+        let answers = [
+            "",
+            "#ACCOUNT",
+            "",
+            "500",
+            "12205bdca17463a5fbb92d461b61ec5b502ab2645c3487c94862f9b18c37bc01c118",
+            "transfer",
+            "n",
+            "#TO",
+            "-1",
+            "100",
+        ]
+        .join("\n");
+        let mut prompter = prompter(&answers);
+
+        let (args, signer) = ask_unit_tx(&mut prompter, &profile, Some(&abi)).unwrap();
+
+        assert_eq!(signer, profile.signer);
+        assert_eq!(args.network, "SKYNET");
+        assert_eq!(args.fuel, 500);
+        let mut expected = std::collections::BTreeMap::new();
+        expected.insert(
+            Value::String(String::from("to")),
+            Value::String(String::from("#TO")),
+        );
+        expected.insert(Value::String(String::from("units")), Value::U64(100));
+        assert_eq!(args.args, Value::Map(expected));
+    }
+
+    #[test]
+    fn repl_command_parse() {
+        assert_eq!(
+            ReplCommand::parse("get_block 10 txs"),
+            Ok(ReplCommand::GetBlock {
+                height: 10,
+                txs: true
+            })
+        );
+        assert_eq!(
+            ReplCommand::parse(" get_receipt 1220ab "),
+            Ok(ReplCommand::GetReceipt {
+                hash: String::from("1220ab")
+            })
+        );
+        assert!(ReplCommand::parse("get_block ten").is_err());
+        assert!(ReplCommand::parse("transfer").is_err());
+    }
+}